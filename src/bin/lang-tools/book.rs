@@ -1,17 +1,32 @@
-use std::{path::PathBuf, ffi::OsStr};
+use std::{path::Path, ffi::OsStr};
 
 use anyhow::Context;
 use console::style;
-use dialoguer::{Confirm, Select};
+use dialoguer::Select;
 use lang_tools::{
     book::{write_toc, Chapter},
-    clipboard::{get_clipboard, set_clipboard},
     file::write_to_file,
-    translation::Translation,
+    glossary::Glossary,
+    translation::{Translation, Translator},
 };
 use tabled::{settings::Style, Table};
 
-use crate::common::{dialoguer_theme, print_info};
+use crate::{
+    common::{dialoguer_theme, print_bracketed_info, print_info, ClipboardCancelled},
+    translator::enforce_glossary,
+};
+
+/// Builds the translator for a chapter given the prompt to show for it.
+pub type BuildTranslator<'a> = dyn Fn(&str) -> Result<Box<dyn Translator>, anyhow::Error> + 'a;
+
+pub fn book_translate_msg(chapter: &Chapter) -> String {
+    format!(
+        "Chapter {} content has been placed in the paste buffer.\n  \
+        Translate the content and then copy these translations into the paste buffer.\n  \
+        Press 'y' to proceed or 'n' to cancel?",
+        chapter.chapter_title
+    )
+}
 
 fn find_first_missing_chapter(chapters: &[Chapter]) -> Option<usize> {
     return chapters.iter().position(|c| !c.path.exists());
@@ -23,7 +38,7 @@ fn style_book_chapter(chapter: &Chapter) -> Result<String, anyhow::Error> {
             "{} Chapter: {} [{} - {} bytes]",
             "✔",
             chapter.chapter_title,
-            chapter.path.file_name().unwrap_or(OsStr::new("")).to_string_lossy().to_string(),
+            chapter.path.file_name().unwrap_or(OsStr::new("")).to_string_lossy(),
             chapter.path.metadata()?.len()
         ))
         .cyan()
@@ -36,43 +51,49 @@ fn style_book_chapter(chapter: &Chapter) -> Result<String, anyhow::Error> {
 }
 
 pub fn prompt_book_translation(
-    toc_path: &PathBuf,
+    toc_path: &Path,
     book_title: &str,
     chapters: &[Chapter],
+    build_translator: &BuildTranslator,
+    glossary: Option<&Glossary>,
 ) -> Result<(), anyhow::Error> {
 
     print_info("Starting book translation session!");
     
-    while let Some(selection) = prompt_for_chapters_list(&chapters)? {
-        prompt_translate_book_chapter(&chapters[selection])?;
-        write_toc(&toc_path, &book_title, &chapters)?;
+    while let Some(selection) = prompt_for_chapters_list(chapters)? {
+        let chapter = &chapters[selection];
+        let mut translator = build_translator(&book_translate_msg(chapter))?;
+
+        match prompt_translate_book_chapter(chapter, translator.as_mut(), glossary) {
+            Err(error) if error.chain().any(|e| e.is::<ClipboardCancelled>()) => {
+                print_bracketed_info("Skipped chapter", &chapter.chapter_title);
+            }
+            result => result?,
+        }
+
+        write_toc(toc_path, book_title, chapters)?;
     }
 
     Ok(())
 }
 
-fn prompt_translate_book_chapter(chapter: &Chapter) -> Result<(), anyhow::Error> {
-    set_clipboard(&chapter.content)?;
+fn prompt_translate_book_chapter(
+    chapter: &Chapter,
+    translator: &mut dyn Translator,
+    glossary: Option<&Glossary>,
+) -> Result<(), anyhow::Error> {
+    print_bracketed_info("Translating chapter", &chapter.chapter_title);
 
-    if Confirm::with_theme(&dialoguer_theme())
-        .with_prompt(format!(
-            "Chapter {} content has been placed in the paste buffer.\n  \
-                Tranlate the content and then copy these translations into the paste buffer.\n  \
-                Press 'y' to proceed or 'n' to cancel?",
-            chapter.chapter_title
-        ))
-        .interact()?
-    {
-        let translation = get_clipboard()?;
+    let mut translations = Translation::translate(&chapter.content, translator)?;
 
-        let translations = 
-            Translation::from_source_and_target(&chapter.content, &translation)?;
+    if let Some(glossary) = glossary {
+        enforce_glossary(glossary, &mut translations);
+    }
 
-        let table = 
-            Table::new(translations).with(Style::markdown()).to_string();
+    let table = 
+        Table::new(translations).with(Style::markdown()).to_string();
 
-        write_to_file(&chapter.path, &table)?;
-    }
+    write_to_file(&chapter.path, &table)?;
 
     Ok(())
 }
@@ -1,4 +1,12 @@
-use clap::{command, Args, Parser, Subcommand};
+use clap::{ArgGroup, Args, Parser, Subcommand, ValueEnum};
+use lang_tools::proportional::Weighting;
+use lang_tools::subtitle::ass::AssOptions;
+use lang_tools::subtitle::bilingual::BilingualOptions;
+use lang_tools::subtitle::layout::LayoutOptions;
+use lang_tools::subtitle::retime::RetimeOptions;
+use lang_tools::translator::{
+    command::ExternalCommandConfig, deepl::DeeplConfig, libre_translate::LibreTranslateConfig, openai::OpenAiConfig,
+};
 use serde::{Deserialize, Serialize};
 use std::{path::PathBuf, str::FromStr};
 use url::Url;
@@ -37,9 +45,13 @@ pub enum Commands {
     ///   sentence on its own line. These sentences are then placed into the
     ///   paste buffer.
     /// - You can then place these subtitles into a tool like Deepl to get translations.
-    /// - Copy these translations and paste them into the editor that is opened.
-    /// - After pressing enter these translaitons are then used to create target
-    ///   language subtitles. These subtitles are then saved locally.
+    /// - Copy these translations into the paste buffer and press 'y'.
+    /// - These translaitons are then used to create target language subtitles.
+    ///   These subtitles are then saved locally.
+    ///
+    /// The paste buffer round trip can be replaced by another translator with the fields
+    /// <translator> or <translators> in ~/.config/lang-tools/config.yaml. The editor
+    /// translator opens $EDITOR to type or paste the translation of each sentence.
     #[command(verbatim_doc_comment)]
     YtTranslate(VideoTranslateArgs),
 
@@ -50,12 +62,31 @@ pub enum Commands {
     ///   sentence on its own line. These sentences are then placed into the
     ///   paste buffer.
     /// - You can then place these subtitles into a tool like Deepl to get translations.
-    /// - Copy these translations and paste them into the editor that is opened.
-    /// - After pressing enter these translaitons are then used to create target
-    ///   language subtitles. These subtitles are then saved locally.
+    /// - Copy these translations into the paste buffer and press 'y'.
+    /// - These translaitons are then used to create target language subtitles.
+    ///   These subtitles are then saved locally.
+    ///
+    /// The paste buffer round trip can be replaced by another translator with the fields
+    /// <translator> or <translators> in ~/.config/lang-tools/config.yaml. The editor
+    /// translator opens $EDITOR to type or paste the translation of each sentence.
     #[command(verbatim_doc_comment)]
     SRTTranslate(SRTTranslateArgs),
 
+    /// Pairs the sentences of two SRT files of the same video by the time they are shown.
+    ///
+    /// Given an SRT file in the source language and one in the target language, such as
+    /// the manual captions of a video in two languages
+    /// - Groups the subtitles of each file into sentences.
+    /// - Pairs the sentences that are shown at the same time.
+    /// - Writes a source/target table of the pairs to --output-file or places it into
+    ///   the paste buffer.
+    /// - Writes a bilingual SRT file with the source text above the target text. The
+    ///   target text is marked as with --bilingual of srt-translate.
+    ///
+    /// The pairs are recorded in the translation memory.
+    #[command(verbatim_doc_comment)]
+    SRTPair(SRTPairArgs),
+
     /// Aids in the translation of text.
     #[command(verbatim_doc_comment)]
     TextTranslate(TextTranslateArgs),
@@ -76,6 +107,16 @@ pub enum Commands {
     /// chapter_path: ~/Dropbox/German/Books/Der Astronaut/Der Astronaut-1.md
     #[command(verbatim_doc_comment)]
     EpubTranslate(EpubTranslateArgs),
+
+    /// Searches, exports and prunes the translation memory.
+    ///
+    /// Every translation made by the translate commands is recorded in the translation
+    /// memory. Sentences found in the memory are not translated again.
+    ///
+    /// The memory is stored in ~/.local/share/lang-tools/translation_memory.json unless
+    /// <translation_memory.path> is set in ~/.config/lang-tools/config.yaml
+    #[command(verbatim_doc_comment)]
+    Tm(TmArgs),
 }
 
 #[derive(Args, Debug)]
@@ -112,7 +153,8 @@ pub struct VideoTranslateArgs {
     #[arg(verbatim_doc_comment)]
     pub target_path: Option<PathBuf>,
 
-    /// The file where the translated subtitles will be written.
+    /// The file where the translated subtitles will be written. A file ending in .vtt
+    /// is written as WebVTT, one ending in .srt as SRT.
     ///
     /// Can not be used with --target-path
     ///
@@ -122,13 +164,49 @@ pub struct VideoTranslateArgs {
     ///     ./<title>.<target_lang>.srt
     #[arg(long, value_parser = PathBuf::from_str)]
     pub target_file: Option<PathBuf>,
+
+    /// Also writes a report of how well each translated cue fits its source cue next to
+    /// the translated subtitles. Cues that received no words, that are much longer or
+    /// shorter than usual or that start in the middle of a sentence are flagged.
+    /// With --sentence-cues every translated sentence is reported against its source
+    /// sentence instead, as the translated cues no longer line up with the source cues.
+    #[arg(long, value_enum)]
+    #[arg(verbatim_doc_comment)]
+    pub report: Option<ReportFormat>,
+
+    /// Writes one translated cue per sentence instead of splitting the sentences over the
+    /// source cues. A cue is shown from the first to the last source cue of its sentence,
+    /// or from its first to its last spoken word for json3 and srv3 subtitles.
+    /// Sentences read faster than <sentence_cues.max_chars_per_second> or longer than
+    /// <sentence_cues.max_cue_seconds> allows are split over several cues. Both are set
+    /// in ~/.config/lang-tools/config.yaml
+    #[arg(long)]
+    #[arg(verbatim_doc_comment)]
+    pub sentence_cues: bool,
+
+    /// Also writes an ASS file next to the translated subtitles that shows the source
+    /// and the translated subtitles together in two styles. The styles can be changed
+    /// with the fields <ass.source> and <ass.target> in ~/.config/lang-tools/config.yaml
+    #[arg(long)]
+    #[arg(verbatim_doc_comment)]
+    pub ass: bool,
+
+    /// Also writes an SRT file next to the translated subtitles whose cues show the
+    /// source text with the translated text below it. The translated text can be marked
+    /// with the fields <bilingual.colour> and <bilingual.italic> in ~/.config/lang-tools/config.yaml
+    /// Can not be used with --sentence-cues as every source cue needs a translated cue
+    /// with the same timing to be shown with.
+    #[arg(long, conflicts_with = "sentence_cues")]
+    #[arg(verbatim_doc_comment)]
+    pub bilingual: bool,
 }
 
 #[derive(Args, Debug)]
 pub struct SRTTranslateArgs {
     #[arg(value_parser = PathBuf::from_str)]
     #[arg(verbatim_doc_comment)]
-    /// The SRT file to be translated.
+    /// The SRT file to be translated. WebVTT (.vtt) and TTML (.ttml, .dfxp, .xml)
+    /// files are read as well.
     ///
     /// The output file will be written to the same directory as source_path
     /// with the file name of
@@ -142,9 +220,78 @@ pub struct SRTTranslateArgs {
     ///
     /// <target_lang> can be defaulted with field <subtitle_target_lang>
     /// in ~/.config/lang-tools/config.yaml
-    #[arg(long, requires = "format")]
+    #[arg(long)]
     #[arg(verbatim_doc_comment)]
     pub target_lang: Option<String>,
+
+    /// Also writes a report of how well each translated cue fits its source cue next to
+    /// the translated subtitles. Cues that received no words, that are much longer or
+    /// shorter than usual or that start in the middle of a sentence are flagged.
+    /// With --sentence-cues every translated sentence is reported against its source
+    /// sentence instead, as the translated cues no longer line up with the source cues.
+    #[arg(long, value_enum)]
+    #[arg(verbatim_doc_comment)]
+    pub report: Option<ReportFormat>,
+
+    /// Writes one translated cue per sentence instead of splitting the sentences over the
+    /// source cues. A cue is shown from the first to the last source cue of its sentence.
+    /// Sentences read faster than <sentence_cues.max_chars_per_second> or longer than
+    /// <sentence_cues.max_cue_seconds> allows are split over several cues. Both are set
+    /// in ~/.config/lang-tools/config.yaml
+    #[arg(long)]
+    #[arg(verbatim_doc_comment)]
+    pub sentence_cues: bool,
+
+    /// Also writes an ASS file next to the translated subtitles that shows the source
+    /// and the translated subtitles together in two styles. The styles can be changed
+    /// with the fields <ass.source> and <ass.target> in ~/.config/lang-tools/config.yaml
+    #[arg(long)]
+    #[arg(verbatim_doc_comment)]
+    pub ass: bool,
+
+    /// Also writes an SRT file next to the translated subtitles whose cues show the
+    /// source text with the translated text below it. The translated text can be marked
+    /// with the fields <bilingual.colour> and <bilingual.italic> in ~/.config/lang-tools/config.yaml
+    /// Can not be used with --sentence-cues as every source cue needs a translated cue
+    /// with the same timing to be shown with.
+    #[arg(long, conflicts_with = "sentence_cues")]
+    #[arg(verbatim_doc_comment)]
+    pub bilingual: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct SRTPairArgs {
+    /// The SRT file in the source language.
+    #[arg(value_parser = PathBuf::from_str)]
+    pub source_path: PathBuf,
+
+    /// The SRT file in the target language.
+    #[arg(value_parser = PathBuf::from_str)]
+    pub target_path: PathBuf,
+
+    /// The language of the source subtitles recorded in the translation memory.
+    #[arg(long)]
+    pub source_lang: Option<String>,
+
+    /// The language of the target subtitles recorded in the translation memory.
+    ///
+    /// <target_lang> can be defaulted with field <subtitle_target_lang>
+    /// in ~/.config/lang-tools/config.yaml
+    #[arg(long)]
+    #[arg(verbatim_doc_comment)]
+    pub target_lang: Option<String>,
+
+    /// The file the source/target table is written to. The table is placed into the
+    /// paste buffer if not provided.
+    #[arg(long, short)]
+    pub output_file: Option<PathBuf>,
+
+    /// The bilingual SRT file.
+    ///
+    /// Defaults to the source_path with the extension bilingual.srt
+    #[arg(long, value_parser = PathBuf::from_str)]
+    #[arg(verbatim_doc_comment)]
+    pub bilingual_file: Option<PathBuf>,
 }
 
 #[derive(Args, Debug)]
@@ -203,7 +350,8 @@ pub struct YtDownloadArgs {
     #[arg(verbatim_doc_comment)]
     pub source_path: Option<PathBuf>,
 
-    /// The file where the source subtitles will be written.
+    /// The file where the source subtitles will be written. A file ending in .vtt
+    /// is written as WebVTT, one ending in .srt as SRT.
     ///
     /// Can not be used with --output_path.
     #[arg(long, value_parser = PathBuf::from_str)]
@@ -230,25 +378,208 @@ pub struct EpubTranslateArgs {
     pub output_path: Option<PathBuf>
 }
 
-#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[derive(Args, Debug)]
+pub struct TmArgs {
+    #[command(subcommand)]
+    pub command: TmCommands,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum TmCommands {
+    /// Lists entries whose source or translation contains the query.
+    Search(TmSearchArgs),
+
+    /// Writes all entries to a file or stdout.
+    Export(TmExportArgs),
+
+    /// Removes the entries matching all of the given filters.
+    Prune(TmPruneArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct TmSearchArgs {
+    pub query: String,
+}
+
+#[derive(Args, Debug)]
+pub struct TmExportArgs {
+    #[arg(long, short, value_enum, default_value_t = TmExportFormat::Json)]
+    pub format: TmExportFormat,
+
+    /// The file the entries are written to. Entries are printed if not provided.
+    #[arg(long, short)]
+    pub output_file: Option<PathBuf>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum ReportFormat {
+    /// A markdown table.
+    Table,
+    Csv,
+    Json,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum TmExportFormat {
+    Json,
+    /// Tab separated source and translation. Can be imported into Anki.
+    Tsv,
+}
+
+#[derive(Args, Debug)]
+#[command(group(ArgGroup::new("filters").required(true).multiple(true)))]
+pub struct TmPruneArgs {
+    /// Only remove entries not used in the given number of days.
+    #[arg(long, group = "filters")]
+    pub older_than_days: Option<u64>,
+
+    /// Only remove entries with this source language.
+    #[arg(long, group = "filters")]
+    pub source_lang: Option<String>,
+
+    /// Only remove entries with this target language.
+    #[arg(long, group = "filters")]
+    pub target_lang: Option<String>,
+
+    /// Only remove entries that were never found in a later translation.
+    #[arg(long, group = "filters")]
+    pub unused: bool,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Default)]
 pub struct Config {
     pub subtitle_target_path: Option<PathBuf>,
     pub subtitle_target_lang: Option<String>,
     pub subtitle_source_path: Option<PathBuf>,
     pub books_target_path: Option<PathBuf>,
+
+    /// The translator used by any command that does not pick its own in <translators>.
+    pub translator: Option<TranslatorKind>,
+
+    /// The translator to use per command.
+    pub translators: Option<CommandTranslators>,
+
+    /// Splits the text given to the translator into chunks of at most this many
+    /// characters. Each chunk is translated separately.
+    pub chunk_max_chars: Option<usize>,
+
+    pub deepl: Option<DeeplConfig>,
+
+    pub libre_translate: Option<LibreTranslateConfig>,
+
+    pub openai: Option<OpenAiConfig>,
+
+    pub external_command: Option<ExternalCommandConfig>,
+
+    pub translation_memory: Option<TranslationMemoryConfig>,
+
+    pub glossary: Option<GlossaryConfig>,
+
+    pub alignment: Option<AlignmentConfig>,
+
+    /// Rewraps translated subtitles to <max_chars_per_line> and <max_lines> when set.
+    pub subtitle_layout: Option<LayoutOptions>,
+
+    /// Limits the reading speed of the cues written with --sentence-cues.
+    pub sentence_cues: Option<RetimeOptions>,
+
+    /// The styles of the ASS files written with --ass.
+    pub ass: Option<AssOptions>,
+
+    /// How the translated text is marked in the files written with --bilingual and srt-pair.
+    pub bilingual: Option<BilingualOptions>,
 }
 
-impl Default for Config {
-    fn default() -> Self {
-        Self {
-            subtitle_target_path: Default::default(),
-            subtitle_target_lang: Default::default(),
-            subtitle_source_path: Default::default(),
-            books_target_path: Default::default(),
-        }
+impl Config {
+    /// Returns the translator configured for a command falling back to <translator> and
+    /// then to the clipboard.
+    pub fn translator_kind(
+        &self,
+        command: fn(&CommandTranslators) -> Option<TranslatorKind>,
+    ) -> TranslatorKind {
+        self.translators
+            .as_ref()
+            .and_then(command)
+            .or(self.translator)
+            .unwrap_or_default()
     }
 }
 
+#[derive(Serialize, Deserialize, PartialEq, Debug, Default, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum TranslatorKind {
+    /// Places the source text in the paste buffer and reads the translation back from it.
+    #[default]
+    Clipboard,
+
+    /// Opens $EDITOR with every source line above an empty line for its translation.
+    Editor,
+
+    /// Translates with the DeepL API configured in <deepl>.
+    Deepl,
+
+    /// Translates with the LibreTranslate server configured in <libre_translate>.
+    LibreTranslate,
+
+    /// Translates with the OpenAI compatible chat completions API configured in <openai>.
+    #[serde(rename = "openai")]
+    OpenAi,
+
+    /// Pipes the source text through the command configured in <external_command>.
+    ExternalCommand,
+}
+
+/// The <translation_memory> section of the config.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Default)]
+pub struct TranslationMemoryConfig {
+    /// The memory is used unless this is set to false.
+    pub enabled: Option<bool>,
+
+    /// Overrides the location of the memory file.
+    pub path: Option<PathBuf>,
+
+    /// The source language recorded when a command can not determine it.
+    pub source_lang: Option<String>,
+
+    /// The target language recorded when a command can not determine it.
+    pub target_lang: Option<String>,
+}
+
+/// The <glossary> section of the config.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Default)]
+pub struct GlossaryConfig {
+    /// Glossaries are applied unless this is set to false.
+    pub enabled: Option<bool>,
+
+    /// The directory holding a <source>-<target>.yaml glossary per language pair.
+    /// Defaults to ~/.config/lang-tools/glossaries
+    pub dir: Option<PathBuf>,
+}
+
+/// The <alignment> section of the config.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Default)]
+pub struct AlignmentConfig {
+    /// Distributes the words within clauses that end in the same punctuation in source and
+    /// target. Enabled unless set to false.
+    pub clauses: Option<bool>,
+
+    /// Gives each source word an equal share of the target (uniform) or a share by its
+    /// length in characters or syllables.
+    pub weighting: Option<Weighting>,
+
+    /// Learns which words translate each other from the translation memory and uses them
+    /// to place the breaks between subtitles. Disabled unless set to true.
+    pub lexicon: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Default)]
+pub struct CommandTranslators {
+    pub srt_translate: Option<TranslatorKind>,
+    pub yt_translate: Option<TranslatorKind>,
+    pub text_translate: Option<TranslatorKind>,
+    pub epub_translate: Option<TranslatorKind>,
+}
+
 fn get_styles() -> clap::builder::Styles {
     clap::builder::Styles::styled()
         .header(
@@ -1,6 +1,7 @@
 use crate::{
     book::prompt_book_translation,
-    cli::{EpubTranslateArgs, Config}, common::{print_info, print_bracketed_info}
+    cli::{EpubTranslateArgs, Config}, common::{print_info, print_bracketed_info},
+    translator::{build_translator, language_pair, load_glossary},
 };
 use epub::doc::EpubDoc;
 use lang_tools::{book::{book_path, epub::get_book_title, toc_path, Chapter}, path::first_path_or_current_dir};
@@ -8,8 +9,12 @@ use lang_tools::{book::{book_path, epub::get_book_title, toc_path, Chapter}, pat
 pub fn exec(args: EpubTranslateArgs, config: Config) -> Result<(), anyhow::Error> {
     let epub = EpubDoc::new(args.input_file)?;
 
+    let pair = language_pair(&config, None, None);
+
+    let glossary = load_glossary(&config, &pair)?;
+
     let base_output_path =
-        first_path_or_current_dir(vec![args.output_path, config.books_target_path])?;
+        first_path_or_current_dir(vec![args.output_path, config.books_target_path.to_owned()])?;
 
     let book_title = get_book_title(&epub)?;
 
@@ -23,7 +28,13 @@ pub fn exec(args: EpubTranslateArgs, config: Config) -> Result<(), anyhow::Error
 
     let chapters = Chapter::from_epub(epub, &book_title, &book_path);
 
-    prompt_book_translation(&toc_path, &book_title, &chapters)?;
+    prompt_book_translation(
+        &toc_path,
+        &book_title,
+        &chapters,
+        &|prompt| build_translator(config.translator_kind(|t| t.epub_translate), &config, prompt, pair.clone()),
+        glossary.as_ref(),
+    )?;
 
     print_info("- Book translation session complete!");
 
@@ -1,6 +1,8 @@
 pub mod epub_translate;
+pub mod srt_pair;
 pub mod srt_translate;
 pub mod text_translate;
+pub mod tm;
 pub mod yt_download;
 pub mod yt_info;
 pub mod yt_translate;
\ No newline at end of file
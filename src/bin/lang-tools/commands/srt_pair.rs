@@ -0,0 +1,79 @@
+use anyhow::anyhow;
+use lang_tools::{
+    clipboard::set_clipboard,
+    file::write_to_file,
+    subtitle::{
+        bilingual::bilingual_cues,
+        clean::clean_subtitles,
+        pair::{pair_cues, pair_subtitles},
+        path::{build_subtitle_path_from_path, load_from_path},
+        write::write_subtitles,
+    },
+    tokenizer::tokenizer_for_lang,
+    translation::Translation,
+};
+use tabled::{settings::Style, Table};
+
+use crate::{
+    cli::{Config, SRTPairArgs},
+    common::{print_bracketed_info, print_info},
+    subtitle::print_subtitles_written_to,
+    translator::{language_pair, record_translations},
+};
+
+pub fn exec(args: SRTPairArgs, config: Config) -> Result<(), anyhow::Error> {
+    let mut source = load_from_path(&args.source_path)?;
+    let mut target = load_from_path(&args.target_path)?;
+
+    let pair = language_pair(
+        &config,
+        args.source_lang,
+        args.target_lang.or(config.subtitle_target_lang.to_owned()),
+    );
+
+    let source_tokenizer = tokenizer_for_lang(&pair.source);
+    let target_tokenizer = tokenizer_for_lang(&pair.target);
+
+    clean_subtitles(&mut source, source_tokenizer.as_ref());
+    clean_subtitles(&mut target, target_tokenizer.as_ref());
+
+    let pairs = pair_subtitles(&source, &target, source_tokenizer.as_ref(), target_tokenizer.as_ref());
+
+    if pairs.is_empty() {
+        return Err(anyhow!("No sentences of the two files are shown at the same time."));
+    }
+
+    print_bracketed_info("Paired sentences", &pairs.len().to_string());
+
+    let translations: Vec<Translation> = pairs.iter().map(|p| p.translation.clone()).collect();
+
+    record_translations(&config, &pair, &translations)?;
+
+    let bilingual_path = args.bilingual_file.unwrap_or(build_subtitle_path_from_path(
+        args.source_path,
+        Some("bilingual".to_owned()),
+        None,
+    ));
+
+    let (source_cues, target_cues) = pair_cues(&pairs);
+    let bilingual = bilingual_cues(&source_cues, &target_cues, &config.bilingual.clone().unwrap_or_default())?;
+
+    let bilingual_path = write_subtitles(&bilingual_path, &bilingual)?;
+
+    print_subtitles_written_to(&bilingual_path);
+
+    let table = Table::new(translations).with(Style::markdown()).to_string();
+
+    match args.output_file {
+        Some(output_file) => {
+            print_bracketed_info("Writing translations to", &output_file.to_string_lossy());
+            write_to_file(&output_file, &table)?
+        }
+        None => {
+            print_info("Placing translations into clipboard.");
+            set_clipboard(&table)?
+        }
+    }
+
+    Ok(())
+}
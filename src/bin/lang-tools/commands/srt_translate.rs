@@ -1,27 +1,50 @@
 use crate::cli::Config;
 use crate::cli::SRTTranslateArgs;
-use crate::common::prompt_for_clipboard_read;
 use crate::subtitle::SUB_TRANSLATE_MSG;
-use crate::subtitle::print_translated_subtitles_written_to;
-use lang_tools::clipboard::set_clipboard;
+use crate::subtitle::{
+    align_options, print_translated_subtitles_written_to, report_alignment, target_subtitles,
+    write_ass, write_bilingual,
+};
+use crate::translator::{build_translator, enforce_glossary, language_pair, load_glossary};
 use lang_tools::subtitle::clean::clean_subtitles;
 use lang_tools::subtitle::path::build_subtitle_path_from_path;
 use lang_tools::subtitle::path::load_from_path;
 use lang_tools::subtitle::write::write_subtitles;
-use lang_tools::subtitle::{extract::extract_text, translation::translated_subtitles};
+use lang_tools::subtitle::extract::extract_text;
+use lang_tools::translation::Translation;
 
 pub fn exec(args: SRTTranslateArgs, config: Config) -> Result<(), anyhow::Error> {
     let mut subtitles = load_from_path(&args.source_path)?;
 
-    clean_subtitles(&mut subtitles);
+    let pair = language_pair(
+        &config,
+        None,
+        args.target_lang.to_owned().or(config.subtitle_target_lang.to_owned()),
+    );
+
+    let options = align_options(&config, &pair)?;
+
+    clean_subtitles(&mut subtitles, options.tokenizer.as_ref());
+
+    let subtitle_text = extract_text(&subtitles, options.tokenizer.as_ref());
 
-    let subtitle_text = extract_text(&subtitles);
+    let mut translator = build_translator(
+        config.translator_kind(|t| t.srt_translate),
+        &config,
+        SUB_TRANSLATE_MSG,
+        pair.clone(),
+    )?;
 
-    set_clipboard(&subtitle_text)?;
+    let glossary = load_glossary(&config, &pair)?;
 
-    let translated_text = prompt_for_clipboard_read(SUB_TRANSLATE_MSG)?;
+    let mut translations = Translation::translate(&subtitle_text, translator.as_mut())?;
 
-    let translated_subs = translated_subtitles(&subtitles, &subtitle_text, &translated_text)?;
+    if let Some(glossary) = &glossary {
+        enforce_glossary(glossary, &mut translations);
+    }
+
+    let translated_subs =
+        target_subtitles(&subtitles, &translations, None, &options, &config, args.sentence_cues)?;
 
     let path = build_subtitle_path_from_path(
         args.source_path,
@@ -33,5 +56,22 @@ pub fn exec(args: SRTTranslateArgs, config: Config) -> Result<(), anyhow::Error>
 
     print_translated_subtitles_written_to(&path);
 
+    if args.ass {
+        write_ass(&subtitles, &translated_subs, &path, config.ass.as_ref())?;
+    }
+
+    if args.bilingual {
+        write_bilingual(&subtitles, &translated_subs, &path, config.bilingual.as_ref())?;
+    }
+
+    report_alignment(
+        &subtitles,
+        &translations,
+        &translated_subs,
+        &path,
+        args.sentence_cues,
+        args.report,
+    )?;
+
     Ok(())
 }
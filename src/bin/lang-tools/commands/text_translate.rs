@@ -1,6 +1,8 @@
 use crate::{
     cli::{Config, TextTranslateArgs},
     common::{print_bracketed_info, print_info, prompt_for_clipboard_read},
+    repair::prompt_repair,
+    translator::{build_translator, enforce_glossary, language_pair, load_glossary, record_translations},
 };
 
 use lang_tools::{
@@ -10,7 +12,7 @@ use lang_tools::{
 };
 use tabled::{settings::Style, Table};
 
-pub fn exec(args: TextTranslateArgs, _config: Config) -> Result<(), anyhow::Error> {
+pub fn exec(args: TextTranslateArgs, config: Config) -> Result<(), anyhow::Error> {
     let content = match args.source_file {
         Some(file) => read_from_file(file)?,
         None => prompt_for_clipboard_read(
@@ -18,14 +20,41 @@ pub fn exec(args: TextTranslateArgs, _config: Config) -> Result<(), anyhow::Erro
         )?,
     };
 
-    let translated = match args.target_file {
-        Some(file) => read_from_file(file)?,
-        None => prompt_for_clipboard_read(
-            "Copy the translated text into past buffer and press 'y'. Or press 'n' to quit",
-        )?,
+    let pair = language_pair(&config, None, None);
+
+    let glossary = load_glossary(&config, &pair)?;
+
+    let mut translations = match args.target_file {
+        Some(file) => {
+            let translated = read_from_file(file)?;
+            let source: Vec<&str> = content.lines().collect();
+            let mut target: Vec<String> = translated.lines().map(|l| l.to_owned()).collect();
+
+            if source.len() != target.len() {
+                target = prompt_repair(&source, target)?;
+            }
+
+            let translations = Translation::from_lines(&source, &target)?;
+
+            record_translations(&config, &pair, &translations)?;
+
+            translations
+        }
+        None => {
+            let mut translator = build_translator(
+                config.translator_kind(|t| t.text_translate),
+                &config,
+                "Copy the translated text into past buffer and press 'y'. Or press 'n' to quit",
+                pair.clone(),
+            )?;
+
+            Translation::translate(content.as_str(), translator.as_mut())?
+        }
     };
 
-    let translations = Translation::from_source_and_target(content.as_str(), translated.as_str())?;
+    if let Some(glossary) = &glossary {
+        enforce_glossary(glossary, &mut translations);
+    }
 
     let table = Table::new(translations).with(Style::markdown()).to_string();
 
@@ -0,0 +1,88 @@
+use anyhow::anyhow;
+use itertools::Itertools;
+use lang_tools::{
+    file::write_to_file,
+    memory::{now, MemoryEntry, TranslationMemory},
+};
+use tabled::{settings::Style, Table};
+
+use crate::{
+    cli::{Config, TmArgs, TmCommands, TmExportArgs, TmExportFormat, TmPruneArgs, TmSearchArgs},
+    common::{print_bracketed_info, print_info},
+    translator::load_translation_memory,
+};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+pub fn exec(args: TmArgs, config: Config) -> Result<(), anyhow::Error> {
+    let memory = load_translation_memory(&config)?
+        .ok_or(anyhow!("The translation memory is disabled in the config."))?;
+
+    print_bracketed_info("Using translation memory", &memory.path().to_string_lossy());
+
+    match args.command {
+        TmCommands::Search(args) => search(args, &memory),
+        TmCommands::Export(args) => export(args, &memory),
+        TmCommands::Prune(args) => prune(args, memory),
+    }
+}
+
+fn search(args: TmSearchArgs, memory: &TranslationMemory) -> Result<(), anyhow::Error> {
+    let entries = memory.search(&args.query);
+
+    if entries.is_empty() {
+        print_info(&format!("No entries found for [{}]", args.query));
+    } else {
+        println!("{}", Table::new(entries).with(Style::markdown()));
+    }
+
+    Ok(())
+}
+
+fn export(args: TmExportArgs, memory: &TranslationMemory) -> Result<(), anyhow::Error> {
+    let entries: Vec<&MemoryEntry> = memory.entries().collect();
+
+    let exported = match args.format {
+        TmExportFormat::Json => serde_json::to_string_pretty(&entries)?,
+        TmExportFormat::Tsv => entries
+            .iter()
+            .map(|e| format!("{}\t{}", e.source_text, e.target_text))
+            .join("\n"),
+    };
+
+    match args.output_file {
+        Some(output_file) => {
+            write_to_file(&output_file, &exported)?;
+            print_bracketed_info(
+                &format!("Exported {} entries to", entries.len()),
+                &output_file.to_string_lossy(),
+            );
+        }
+        None => println!("{}", exported),
+    }
+
+    Ok(())
+}
+
+fn prune(args: TmPruneArgs, mut memory: TranslationMemory) -> Result<(), anyhow::Error> {
+    let used_before = args
+        .older_than_days
+        .map(|days| now().saturating_sub(days * SECONDS_PER_DAY));
+
+    let removed = memory.prune(|e| {
+        used_before.is_none_or(|t| e.last_used < t)
+            && args.source_lang.as_ref().is_none_or(|l| &e.source_lang == l)
+            && args.target_lang.as_ref().is_none_or(|l| &e.target_lang == l)
+            && (!args.unused || e.hits == 0)
+    });
+
+    memory.save()?;
+
+    print_info(&format!(
+        "Removed {} entries. {} entries remain.",
+        removed,
+        memory.len()
+    ));
+
+    Ok(())
+}
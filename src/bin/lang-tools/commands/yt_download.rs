@@ -6,7 +6,13 @@ use crate::{
 use anyhow::Context;
 
 use lang_tools::{
-    subtitle::{clean::clean_subtitles, path::build_subtitle_path, write::write_subtitles},
+    subtitle::{
+        clean::clean_subtitles,
+        format::SubtitleFormat,
+        path::build_subtitle_path,
+        write::{write_subtitles, write_vtt},
+    },
+    tokenizer::tokenizer_for_lang,
     youtube,
 };
 
@@ -18,20 +24,35 @@ pub fn exec(args: YtDownloadArgs, config: Config) -> Result<(), anyhow::Error> {
         selecte_subtitle(args.lang, args.format, &info.choices)
             .context("Exiting...")?;
     
-    let mut subtitles = youtube::download(&subtitle_choice.location)?;
-
-    clean_subtitles(&mut subtitles);
-
     let path = build_subtitle_path(
         args.source_file,
         args.source_path,
         config.subtitle_source_path,
         &info.name,
-        &Some(subtitle_choice.lang),
+        &Some(subtitle_choice.lang.to_owned()),
         &info.channel,
     )?;
 
-    let path = write_subtitles(&path, &subtitles)?;
+    let tokenizer = tokenizer_for_lang(&subtitle_choice.lang);
+
+    // WebVTT written as WebVTT keeps its cue settings and blocks. Only the text is cleaned.
+    let keep_vtt = SubtitleFormat::from_name(&subtitle_choice.format) == Some(SubtitleFormat::Vtt)
+        && SubtitleFormat::from_path(&path) == Some(SubtitleFormat::Vtt);
+
+    let path = if keep_vtt {
+        let vtt = youtube::download_vtt(&subtitle_choice)?;
+        let mut subtitles = vtt.to_subtitles();
+
+        clean_subtitles(&mut subtitles, tokenizer.as_ref());
+
+        write_vtt(&path, &vtt.with_subtitles(&subtitles)?)?
+    } else {
+        let mut subtitles = youtube::download(&subtitle_choice)?;
+
+        clean_subtitles(&mut subtitles, tokenizer.as_ref());
+
+        write_subtitles(&path, &subtitles)?
+    };
     print_subtitles_written_to(&path);
 
     Ok(())
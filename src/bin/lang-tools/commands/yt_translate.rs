@@ -1,16 +1,16 @@
 use crate::cli::{Config, VideoTranslateArgs};
-use crate::common::prompt_for_clipboard_read;
 use crate::subtitle::{
-    fetch_video_info, print_translated_subtitles_written_to, selecte_subtitle, SUB_TRANSLATE_MSG,
+    align_options, download_subtitles, fetch_video_info, print_translated_subtitles_written_to,
+    report_alignment, selecte_subtitle, target_subtitles, write_ass,
+    write_bilingual, SUB_TRANSLATE_MSG,
 };
+use crate::translator::{build_translator, enforce_glossary, language_pair, load_glossary};
 use anyhow::Result;
-use lang_tools::clipboard::set_clipboard;
 use lang_tools::subtitle::clean::clean_subtitles;
 use lang_tools::subtitle::extract::extract_text;
 use lang_tools::subtitle::path::build_subtitle_path;
-use lang_tools::subtitle::translation::translated_subtitles;
 use lang_tools::subtitle::write::write_subtitles;
-use lang_tools::youtube;
+use lang_tools::translation::Translation;
 
 pub fn exec(args: VideoTranslateArgs, config: Config) -> Result<(), anyhow::Error> {
     let info = fetch_video_info(&args.download_args.video_args.url)?;
@@ -21,14 +21,30 @@ pub fn exec(args: VideoTranslateArgs, config: Config) -> Result<(), anyhow::Erro
         &info.choices,
     )?;
 
-    let mut subtitles = youtube::download(&choice.location)?;
+    let pair = language_pair(
+        &config,
+        Some(choice.lang.to_owned()),
+        args.target_lang.to_owned().or(config.subtitle_target_lang.to_owned()),
+    );
 
-    clean_subtitles(&mut subtitles);
+    let mut translator = build_translator(
+        config.translator_kind(|t| t.yt_translate),
+        &config,
+        SUB_TRANSLATE_MSG,
+        pair.clone(),
+    )?;
+
+    let glossary = load_glossary(&config, &pair)?;
+    let options = align_options(&config, &pair)?;
+
+    let (mut subtitles, word_times) = download_subtitles(&choice, options.tokenizer.as_ref())?;
+
+    clean_subtitles(&mut subtitles, options.tokenizer.as_ref());
 
     let source_path = build_subtitle_path(
         args.download_args.source_file,
         args.download_args.source_path,
-        config.subtitle_source_path,
+        config.subtitle_source_path.to_owned(),
         &info.name,
         &Some(choice.lang),
         &info.channel,
@@ -37,13 +53,22 @@ pub fn exec(args: VideoTranslateArgs, config: Config) -> Result<(), anyhow::Erro
     let source_path = write_subtitles(&source_path, &subtitles)?;
     println!("Source subtitles saved to [{:?}]", source_path);
 
-    let subtitle_text = extract_text(&subtitles);
+    let subtitle_text = extract_text(&subtitles, options.tokenizer.as_ref());
 
-    set_clipboard(&subtitle_text)?;
+    let mut translations = Translation::translate(&subtitle_text, translator.as_mut())?;
 
-    let translated_text = prompt_for_clipboard_read(SUB_TRANSLATE_MSG)?;
+    if let Some(glossary) = &glossary {
+        enforce_glossary(glossary, &mut translations);
+    }
 
-    let target_subs = translated_subtitles(&subtitles, &subtitle_text, &translated_text)?;
+    let target_subs = target_subtitles(
+        &subtitles,
+        &translations,
+        word_times.as_deref(),
+        &options,
+        &config,
+        args.sentence_cues,
+    )?;
 
     let target_path = build_subtitle_path(
         args.target_file,
@@ -58,5 +83,22 @@ pub fn exec(args: VideoTranslateArgs, config: Config) -> Result<(), anyhow::Erro
 
     print_translated_subtitles_written_to(&target_path);
 
+    if args.ass {
+        write_ass(&subtitles, &target_subs, &target_path, config.ass.as_ref())?;
+    }
+
+    if args.bilingual {
+        write_bilingual(&subtitles, &target_subs, &target_path, config.bilingual.as_ref())?;
+    }
+
+    report_alignment(
+        &subtitles,
+        &translations,
+        &target_subs,
+        &target_path,
+        args.sentence_cues,
+        args.report,
+    )?;
+
     Ok(())
 }
@@ -1,9 +1,9 @@
-use anyhow::anyhow;
 use console::Style;
 use console::style;
 use dialoguer::{theme::ColorfulTheme, Confirm};
 use lang_tools::clipboard::get_clipboard;
 use lazy_static::lazy_static;
+use thiserror::Error;
 
 lazy_static! {
     pub static ref MSG_STYLE: Style = Style::new().cyan();
@@ -13,11 +13,17 @@ pub fn dialoguer_theme() -> ColorfulTheme {
     ColorfulTheme::default()
 }
 
+/// Returned when the user answers 'n' instead of copying translations into the paste
+/// buffer, so that callers can tell skipping apart from failing.
+#[derive(Debug, Error)]
+#[error("User cancelled reading from clipboard")]
+pub struct ClipboardCancelled;
+
 pub fn prompt_for_clipboard_read(msg: &str) -> Result<String, anyhow::Error> {
     if Confirm::new().with_prompt(msg).interact()? {
         get_clipboard()
     } else {
-        Err(anyhow!("User cancelled reading from clipboard"))
+        Err(ClipboardCancelled.into())
     }
 }
 
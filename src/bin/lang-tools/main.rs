@@ -1,3 +1,5 @@
+#![allow(clippy::needless_return)]
+
 use std::{path::{PathBuf, Path}, fs::File, io::Read};
 
 use anyhow::Context;
@@ -10,7 +12,9 @@ pub mod commands;
 pub mod common;
 pub mod cli;
 pub mod book;
+pub mod repair;
 pub mod subtitle;
+pub mod translator;
 
 lazy_static! {
     pub static ref CONFIG_PATH: String = shellexpand::tilde("~/.config/lang-tools/config.yaml").to_string();
@@ -33,11 +37,13 @@ fn try_main() -> Result<(), anyhow::Error> {
 
     match cli.command {
         Commands::SRTTranslate(args) => commands::srt_translate::exec(args, config),
+        Commands::SRTPair(args) => commands::srt_pair::exec(args, config),
         Commands::YtTranslate(args) => commands::yt_translate::exec(args, config),
         Commands::YtInfo(args) => commands::yt_info::exec(args, config),
         Commands::YtDownload(args) => commands::yt_download::exec(args, config),
         Commands::TextTranslate(args) => commands::text_translate::exec(args, config),
-        Commands::EpubTranslate(args) => commands::epub_translate::exec(args, config)
+        Commands::EpubTranslate(args) => commands::epub_translate::exec(args, config),
+        Commands::Tm(args) => commands::tm::exec(args, config),
     }
 }
 
@@ -48,7 +54,7 @@ fn load_config(path: PathBuf) -> Result<Config, anyhow::Error> {
         let mut config = String::new();
         f.read_to_string(&mut config).unwrap();
     
-        return serde_yaml::from_str(&config.as_str())
+        return serde_yaml::from_str(config.as_str())
             .context(format!("Failed loading config from {}", path.into_os_string().into_string().unwrap()))
     } else {
         return Ok(Config::default())
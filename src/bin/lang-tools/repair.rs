@@ -0,0 +1,225 @@
+use anyhow::anyhow;
+use dialoguer::{Input, Select};
+use lang_tools::translator::repair::{find_misalignment, Repair};
+use tabled::{builder::Builder, settings::Style};
+
+use crate::common::{dialoguer_theme, print_error, print_info};
+
+/// The number of lines shown before and after the suspicious lines.
+const CONTEXT_LINES: usize = 3;
+
+const ACTIONS: [&str; 6] = [
+    "Merge a target line with the next",
+    "Split a target line",
+    "Edit a target line",
+    "Insert a target line",
+    "Delete a target line",
+    "Abort",
+];
+
+/// Lets the user fix the target lines until there is one for every source line. Each round
+/// shows the place where lines were most likely merged or split next to each other.
+pub fn prompt_repair(source: &[&str], mut target: Vec<String>) -> Result<Vec<String>, anyhow::Error> {
+    if !console::user_attended() {
+        return Err(mismatch(source.len(), target.len()));
+    }
+
+    while source.len() != target.len() {
+        print_error(&format!(
+            "There are {} lines in source and {} lines in target.",
+            source.len(),
+            target.len()
+        ));
+
+        let misalignment = find_misalignment(source, &target).ok_or_else(|| mismatch(source.len(), target.len()))?;
+
+        let source_region = region(misalignment.bead.source.clone(), source.len());
+        let target_region = region(misalignment.bead.target.clone(), target.len());
+
+        print_side_by_side(source, &target, source_region, target_region.clone());
+
+        let mut items = vec![format!("Apply suggestion: {}", misalignment.suggestion)];
+        items.extend(ACTIONS.iter().map(|a| a.to_string()));
+
+        let selection = Select::with_theme(&dialoguer_theme())
+            .with_prompt("The lines most likely went wrong here. How do you want to fix them?")
+            .items(&items)
+            .default(0)
+            .interact_opt()?;
+
+        let repair = match selection {
+            Some(0) => Some(misalignment.suggestion),
+            Some(1) => prompt_target_line(&target, target_region)?
+                .map(|index| Repair::Merge { target: index }),
+            Some(2) => prompt_split(&target, target_region)?,
+            Some(3) => match prompt_target_line(&target, target_region)? {
+                Some(index) => Some(Repair::Edit {
+                    target: index,
+                    text: prompt_text("Translation", &target[index])?,
+                }),
+                None => None,
+            },
+            Some(4) => match prompt_insert_position(&target, target_region)? {
+                Some(index) => Some(Repair::Insert {
+                    target: index,
+                    text: prompt_text("Translation of the inserted line", "")?,
+                }),
+                None => None,
+            },
+            Some(5) => prompt_target_line(&target, target_region)?
+                .map(|index| Repair::Delete { target: index }),
+            _ => return Err(anyhow!("User cancelled repairing the translation")),
+        };
+
+        if let Some(repair) = repair {
+            repair.apply(&mut target);
+        }
+    }
+
+    print_info("Source and target now have the same number of lines.");
+
+    Ok(target)
+}
+
+fn mismatch(source_len: usize, target_len: usize) -> anyhow::Error {
+    anyhow!(
+        "There are {} lines in source and {} lines in target. Number of lines must be of equal length",
+        source_len,
+        target_len
+    )
+}
+
+fn region(lines: std::ops::Range<usize>, len: usize) -> std::ops::Range<usize> {
+    lines.start.saturating_sub(CONTEXT_LINES)..(lines.end + CONTEXT_LINES).min(len)
+}
+
+fn print_side_by_side(
+    source: &[&str],
+    target: &[String],
+    source_region: std::ops::Range<usize>,
+    target_region: std::ops::Range<usize>,
+) {
+    let mut builder = Builder::new();
+    builder.set_header(["#", "Source", "#", "Target"]);
+
+    let rows = source_region.len().max(target_region.len());
+
+    for row in 0..rows {
+        let s = source_region.start + row;
+        let t = target_region.start + row;
+
+        builder.push_record([
+            cell_number(s, &source_region),
+            cell_text(source, s, &source_region),
+            cell_number(t, &target_region),
+            cell_text(target, t, &target_region),
+        ]);
+    }
+
+    println!("{}", builder.build().with(Style::rounded()));
+}
+
+fn cell_number(index: usize, region: &std::ops::Range<usize>) -> String {
+    if region.contains(&index) {
+        format!("{}", index + 1)
+    } else {
+        String::new()
+    }
+}
+
+fn cell_text<S: AsRef<str>>(lines: &[S], index: usize, region: &std::ops::Range<usize>) -> String {
+    if region.contains(&index) {
+        lines[index].as_ref().to_owned()
+    } else {
+        String::new()
+    }
+}
+
+/// The region moved onto the nearest target lines when it holds none of them, such as when
+/// the lines missing from the target are its last lines. Empty only if the target is.
+fn nearest_region(region: std::ops::Range<usize>, len: usize) -> std::ops::Range<usize> {
+    if len == 0 {
+        return 0..0;
+    }
+
+    let start = region.start.min(len - 1);
+    start..region.end.max(start + 1).min(len)
+}
+
+fn prompt_target_line(
+    target: &[String],
+    region: std::ops::Range<usize>,
+) -> Result<Option<usize>, anyhow::Error> {
+    let region = nearest_region(region, target.len());
+
+    if region.is_empty() {
+        print_error("There are no target lines to pick from.");
+        return Ok(None);
+    }
+
+    let items: Vec<String> = region
+        .clone()
+        .map(|index| format!("[{}] {}", index + 1, target[index]))
+        .collect();
+
+    Ok(Select::with_theme(&dialoguer_theme())
+        .with_prompt("Pick a target line. Press 'esc' to go back.")
+        .items(&items)
+        .default(0)
+        .interact_opt()?
+        .map(|selection| region.start + selection))
+}
+
+/// Asks where a target line is inserted. A line can be inserted before any line of the
+/// region or after its last line, so that a missing last line can be added.
+fn prompt_insert_position(
+    target: &[String],
+    region: std::ops::Range<usize>,
+) -> Result<Option<usize>, anyhow::Error> {
+    let region = nearest_region(region, target.len());
+
+    let mut items: Vec<String> = region
+        .clone()
+        .map(|index| format!("Before [{}] {}", index + 1, target[index]))
+        .collect();
+
+    items.push(match region.end {
+        0 => "As the first line".to_owned(),
+        end => format!("After [{}] {}", end, target[end - 1]),
+    });
+
+    Ok(Select::with_theme(&dialoguer_theme())
+        .with_prompt("Where should the line be inserted? Press 'esc' to go back.")
+        .items(&items)
+        .default(0)
+        .interact_opt()?
+        .map(|selection| region.start + selection))
+}
+
+fn prompt_split(
+    target: &[String],
+    region: std::ops::Range<usize>,
+) -> Result<Option<Repair>, anyhow::Error> {
+    let index = match prompt_target_line(target, region)? {
+        Some(index) => index,
+        None => return Ok(None),
+    };
+
+    let start = prompt_text("Text that starts the new line", "")?;
+
+    match target[index].find(start.trim()) {
+        Some(at) if at > 0 && !start.trim().is_empty() => Ok(Some(Repair::Split { target: index, at })),
+        _ => {
+            print_error(&format!("[{}] was not found after the start of the line.", start));
+            Ok(None)
+        }
+    }
+}
+
+fn prompt_text(prompt: &str, initial: &str) -> Result<String, anyhow::Error> {
+    Ok(Input::<String>::with_theme(&dialoguer_theme())
+        .with_prompt(prompt)
+        .with_initial_text(initial)
+        .allow_empty(true)
+        .interact_text()?)
+}
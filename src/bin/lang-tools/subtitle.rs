@@ -1,19 +1,48 @@
-use std::path::PathBuf;
+use std::path::Path;
 
 use anyhow::anyhow;
-use dialoguer::Select;
-use lang_tools::youtube::{self, SubtitleChoice, VideoInfo};
+use dialoguer::{Confirm, Select};
+use itertools::Itertools;
+use lang_tools::{
+    file::write_to_file,
+    lexicon::{Lexicon, TRAINING_ITERATIONS},
+    memory::LanguagePair,
+    segmenter::Segmenter,
+    tokenizer::Tokenizer,
+    subtitle::{
+        ass::AssOptions,
+        bilingual::{bilingual_cues, BilingualOptions},
+        format::SubtitleFormat,
+        timedtext::WordTimes,
+        layout::rewrap_subtitles,
+        report::{alignment_report, sentence_report, summary, to_csv},
+        retime::retime_by_sentence,
+        translation::{align_by_word_count, align_with, AlignOptions},
+        write::{write_dual_subtitles, write_subtitles},
+    },
+    translation::Translation,
+    youtube::{self, SubtitleChoice, VideoInfo},
+};
+use srtlib::Subtitles;
+use tabled::{settings::Style, Table};
 use url::Url;
 
-use crate::common::{dialoguer_theme, print_bracketed_info};
+use crate::{
+    cli::{Config, ReportFormat},
+    common::{dialoguer_theme, print_bracketed_info, print_error, print_info},
+    translator::load_translation_memory,
+};
 
-pub static SUB_TRANSLATE_MSG: &'static str =
+/// The number of translations of a language pair needed to learn a lexicon from them.
+const MIN_LEXICON_TRANSLATIONS: usize = 200;
+
+pub static SUB_TRANSLATE_MSG: &str =
     "Copy subtitle translations to paste buffer and press 'y'. Or press 'n' to quit";
 
 pub fn fetch_video_info(url: &Url) -> Result<VideoInfo, anyhow::Error> {
-    print_bracketed_info("Fetching info for video", &url.as_str());
+    print_bracketed_info("Fetching info for video", url.as_str());
 
-    let info = youtube::info(&url);
+    let info = youtube::info(url);
 
     if let Ok(info) = &info {
         print_bracketed_info("Found info for title", &info.name);
@@ -29,7 +58,7 @@ pub fn selecte_subtitle(
 ) -> Result<SubtitleChoice, anyhow::Error> {
     match (lang, format) {
         (Some(lang), Some(format)) => choices
-            .into_iter()
+            .iter()
             .find(|choice| choice.lang == lang && choice.format == format)
             .ok_or(anyhow!("Failed to pick choice of subtitles"))
             .cloned(),
@@ -37,21 +66,230 @@ pub fn selecte_subtitle(
     }
 }
 
+/// Downloads the subtitles of the choice together with the times their words are spoken
+/// when the format gives them.
+pub fn download_subtitles(
+    choice: &SubtitleChoice,
+    tokenizer: &dyn Tokenizer,
+) -> Result<(Subtitles, Option<WordTimes>), anyhow::Error> {
+    match SubtitleFormat::from_name(&choice.format) {
+        Some(SubtitleFormat::Json3 | SubtitleFormat::Srv3) => {
+            let timed_text = youtube::download_timed_text(choice)?;
+            Ok((timed_text.to_subtitles(), Some(timed_text.word_times(tokenizer))))
+        }
+        _ => Ok((youtube::download(choice)?, None)),
+    }
+}
+
 pub fn prompt_subtitle_choice(choices: &[SubtitleChoice]) -> Result<SubtitleChoice, anyhow::Error> {
     Select::with_theme(&dialoguer_theme())
         .with_prompt("Choose a subtitle and press enter. Or hit 'esc' or 'q' to exit")
-        .items(&choices)
+        .items(choices)
         .default(0)
         .interact_opt()?
         .map(|selection| choices[selection].clone())
         .ok_or(anyhow!("No subtitle was selected"))
 }
 
-pub fn print_translated_subtitles_written_to(path: &PathBuf) {
+/// The alignment options suited to the target language with the settings of <alignment>.
+pub fn align_options(config: &Config, pair: &LanguagePair) -> Result<AlignOptions, anyhow::Error> {
+    let defaults = AlignOptions::for_langs(&pair.source, &pair.target);
+
+    let alignment = match &config.alignment {
+        Some(alignment) => alignment,
+        None => return Ok(defaults),
+    };
+
+    let lexicon = match alignment.lexicon {
+        Some(true) => load_lexicon(config, pair, defaults.tokenizer.as_ref(), defaults.segmenter.as_ref())?,
+        _ => None,
+    };
+
+    Ok(AlignOptions {
+        clauses: alignment.clauses.unwrap_or(defaults.clauses),
+        weighting: alignment.weighting.unwrap_or(defaults.weighting),
+        lexicon,
+        ..defaults
+    })
+}
+
+/// Learns a lexicon from the translation memory of the language pair. None if there are
+/// too few translations to learn from.
+fn load_lexicon(
+    config: &Config,
+    pair: &LanguagePair,
+    tokenizer: &dyn Tokenizer,
+    segmenter: &dyn Segmenter,
+) -> Result<Option<Lexicon>, anyhow::Error> {
+    let translations = match load_translation_memory(config)? {
+        Some(memory) => memory.translations(pair),
+        None => return Ok(None),
+    };
+
+    if translations.len() < MIN_LEXICON_TRANSLATIONS {
+        print_info(&format!(
+            "Only {} translations in the memory. At least {} are needed to learn word alignments.",
+            translations.len(),
+            MIN_LEXICON_TRANSLATIONS
+        ));
+        return Ok(None);
+    }
+
+    print_bracketed_info("Learning word alignments from translations", &translations.len().to_string());
+
+    Ok(Some(Lexicon::train(&translations, tokenizer, segmenter, TRAINING_ITERATIONS)))
+}
+
+/// Aligns the translations with the subtitles. When the words can not be matched the user
+/// is offered to distribute the translated words by word count instead.
+pub fn align_subtitles(
+    subtitles: &Subtitles,
+    translations: &[Translation],
+    options: &AlignOptions,
+) -> Result<Subtitles, anyhow::Error> {
+    let error = match align_with(subtitles, translations, options) {
+        Ok(aligned) => return Ok(aligned),
+        Err(error) => error,
+    };
+
+    print_error(&format!("Failed to align the translation: {}", error));
+
+    if console::user_attended()
+        && Confirm::with_theme(&dialoguer_theme())
+            .with_prompt("Distribute the translated words over the subtitles by word count instead?")
+            .interact()?
+    {
+        return Ok(align_by_word_count(subtitles, translations, options));
+    }
+
+    Err(error.into())
+}
+
+/// The translated subtitles with one cue per sentence if sentence_cues is set. Otherwise the
+/// translations are aligned with the subtitles and rewrapped to <subtitle_layout> if it is
+/// set. Sentence cues are always wrapped, to the default layout if none is set, and start
+/// and end with the spoken words when their times are known.
+pub fn target_subtitles(
+    subtitles: &Subtitles,
+    translations: &[Translation],
+    word_times: Option<&[Vec<u64>]>,
+    options: &AlignOptions,
+    config: &Config,
+    sentence_cues: bool,
+) -> Result<Subtitles, anyhow::Error> {
+    let segmenter = options.segmenter.as_ref();
+
+    if sentence_cues {
+        return retime_by_sentence(
+            subtitles,
+            translations,
+            word_times,
+            &config.sentence_cues.unwrap_or_default(),
+            &config.subtitle_layout.unwrap_or_default(),
+            options.tokenizer.as_ref(),
+            segmenter,
+        );
+    }
+
+    let mut target = align_subtitles(subtitles, translations, options)?;
+
+    if let Some(layout) = &config.subtitle_layout {
+        rewrap_subtitles(&mut target, layout, segmenter);
+    }
+
+    Ok(target)
+}
+
+/// Prints how many translated cues look suspicious and writes the full report next to the
+/// translated subtitles if a format is given. With sentence cues the translated cues do not
+/// line up with the source cues, so every translated sentence is reported instead.
+pub fn report_alignment(
+    source: &Subtitles,
+    translations: &[Translation],
+    target: &Subtitles,
+    target_path: &Path,
+    sentence_cues: bool,
+    format: Option<ReportFormat>,
+) -> Result<(), anyhow::Error> {
+    let (reports, unit) = match sentence_cues {
+        true => (sentence_report(translations), "sentences"),
+        false => (alignment_report(source, target), "cues"),
+    };
+
+    let flagged = reports.iter().filter(|r| r.is_flagged()).count();
+
+    let counts = summary(&reports)
+        .into_iter()
+        .filter(|(_, count)| *count > 0)
+        .map(|(flag, count)| format!("{} {}", count, flag))
+        .join(", ");
+
+    if flagged == 0 {
+        print_info(&format!("All {} translated {} look fine.", reports.len(), unit));
+    } else {
+        print_error(&format!(
+            "{} of {} translated {} look suspicious: {}",
+            flagged,
+            reports.len(),
+            unit,
+            counts
+        ));
+    }
+
+    let (content, extension) = match format {
+        Some(ReportFormat::Table) => (
+            Table::new(&reports).with(Style::markdown()).to_string(),
+            "report.md",
+        ),
+        Some(ReportFormat::Csv) => (to_csv(&reports), "report.csv"),
+        Some(ReportFormat::Json) => (serde_json::to_string_pretty(&reports)?, "report.json"),
+        None => return Ok(()),
+    };
+
+    let path = target_path.with_extension(extension);
+    write_to_file(&path, &content)?;
+    print_bracketed_info("Alignment report saved to", &path.to_string_lossy());
+
+    Ok(())
+}
+
+/// Writes the source and the translated subtitles into an ASS file next to the translated
+/// subtitles.
+pub fn write_ass(
+    source: &Subtitles,
+    target: &Subtitles,
+    target_path: &Path,
+    options: Option<&AssOptions>,
+) -> Result<(), anyhow::Error> {
+    let options = options.cloned().unwrap_or_default();
+    let path = write_dual_subtitles(&target_path.with_extension("ass"), source, target, &options)?;
+
+    print_bracketed_info("Dual language subtitles saved to", &path.to_string_lossy());
+
+    Ok(())
+}
+
+/// Writes an SRT file next to the translated subtitles whose cues hold the source text
+/// with the translated text below it.
+pub fn write_bilingual(
+    source: &Subtitles,
+    target: &Subtitles,
+    target_path: &Path,
+    options: Option<&BilingualOptions>,
+) -> Result<(), anyhow::Error> {
+    let bilingual = bilingual_cues(source, target, &options.cloned().unwrap_or_default())?;
+    let path = write_subtitles(&target_path.with_extension("bilingual.srt"), &bilingual)?;
+
+    print_bracketed_info("Bilingual subtitles saved to", &path.to_string_lossy());
+
+    Ok(())
+}
+
+pub fn print_translated_subtitles_written_to(path: &Path) {
     print_bracketed_info("Translated subtitles saved to", &path.to_string_lossy());
 }
 
-pub fn print_subtitles_written_to(path: &PathBuf) {
+pub fn print_subtitles_written_to(path: &Path) {
     print_bracketed_info("Subtitles saved to", &path.to_string_lossy());
 }
 
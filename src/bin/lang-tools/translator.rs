@@ -0,0 +1,255 @@
+use anyhow::{anyhow, Context};
+use dialoguer::{Confirm, Editor};
+use lang_tools::{
+    clipboard::set_clipboard,
+    editor::{missing_translations, parse_editor_text, to_editor_text},
+    glossary::Glossary,
+    memory::{LanguagePair, MemoryTranslator, TranslationMemory},
+    segmenter::segmenter_for_lang,
+    translation::{Translation, Translator},
+    translator::{
+        chunk::ChunkedTranslator, command::ExternalCommandTranslator, deepl::DeeplTranslator,
+        libre_translate::LibreTranslateTranslator, openai::OpenAiTranslator,
+        repair::RepairingTranslator,
+    },
+};
+
+use std::path::PathBuf;
+
+use tabled::{settings::Style, Table};
+
+use crate::{
+    cli::{Config, TranslatorKind},
+    common::{dialoguer_theme, print_bracketed_info, print_error, prompt_for_clipboard_read},
+    repair::prompt_repair,
+};
+
+/// The directory searched for glossaries unless <glossary.dir> is set.
+const GLOSSARY_DIR: &str = "~/.config/lang-tools/glossaries";
+
+/// The number of untranslated lines listed after closing the editor.
+const MAX_MISSING_SHOWN: usize = 10;
+
+/// Translates by placing the source lines in the paste buffer and waiting for the user to
+/// copy the translations back into it.
+pub struct ClipboardTranslator {
+    prompt: String,
+}
+
+impl ClipboardTranslator {
+    pub fn new(prompt: &str) -> Self {
+        ClipboardTranslator {
+            prompt: prompt.to_owned(),
+        }
+    }
+}
+
+impl Translator for ClipboardTranslator {
+    fn translate(&mut self, source: &[&str]) -> Result<Vec<String>, anyhow::Error> {
+        set_clipboard(&source.join("\n"))?;
+
+        let translated = prompt_for_clipboard_read(&self.prompt)?;
+
+        Ok(translated.lines().map(|l| l.to_owned()).collect())
+    }
+}
+
+/// Translates by opening $EDITOR with each source line as a comment above an empty line
+/// for its translation. The editor is opened again while translations are missing.
+pub struct EditorTranslator;
+
+impl Translator for EditorTranslator {
+    fn translate(&mut self, source: &[&str]) -> Result<Vec<String>, anyhow::Error> {
+        let mut target: Vec<String> = vec![String::new(); source.len()];
+        let mut missing: Vec<usize> = Vec::new();
+
+        loop {
+            let text = to_editor_text(source, &target, &missing);
+
+            let edited = Editor::new()
+                .extension(".txt")
+                .edit(&text)
+                .context("Failed to open editor. Make sure $EDITOR is set")?
+                .ok_or(anyhow!("The editor was closed without saving."))?;
+
+            target = parse_editor_text(&edited, source.len());
+            missing = missing_translations(source, &target);
+
+            if missing.is_empty() {
+                return Ok(target);
+            }
+
+            print_error(&format!("{} lines have no translation:", missing.len()));
+
+            for index in missing.iter().take(MAX_MISSING_SHOWN) {
+                print_error(&format!("  [{}] {}", index + 1, source[*index]));
+            }
+
+            if !Confirm::with_theme(&dialoguer_theme())
+                .with_prompt("Open the editor again to add them?")
+                .interact()?
+            {
+                return Err(anyhow!("User cancelled translating in the editor"));
+            }
+        }
+    }
+}
+
+/// Builds the translator of the given kind. The prompt is shown by translators that need
+/// the user to act before the translation can continue. Unless disabled the translator
+/// first looks up lines in the translation memory for the language pair.
+pub fn build_translator(
+    kind: TranslatorKind,
+    config: &Config,
+    prompt: &str,
+    pair: LanguagePair,
+) -> Result<Box<dyn Translator>, anyhow::Error> {
+    let mut translator: Box<dyn Translator> = Box::new(RepairingTranslator::new(
+        build_backend(kind, config, prompt)?,
+        Box::new(prompt_repair),
+    ));
+
+    if let Some(max_chars) = config.chunk_max_chars {
+        translator = Box::new(
+            ChunkedTranslator::new(translator, max_chars)
+                .with_progress(Box::new(print_chunk_progress))
+                .with_separator(segmenter_for_lang(&pair.target).separator()),
+        );
+    }
+
+    match load_translation_memory(config)? {
+        Some(memory) => Ok(Box::new(MemoryTranslator::new(memory, pair, translator))),
+        None => Ok(translator),
+    }
+}
+
+/// The language pair of a translation falling back to the languages configured in
+/// <translation_memory> when the command can not determine them.
+pub fn language_pair(config: &Config, source: Option<String>, target: Option<String>) -> LanguagePair {
+    let memory_config = config.translation_memory.as_ref();
+
+    LanguagePair::new(
+        source.or(memory_config.and_then(|c| c.source_lang.to_owned())),
+        target.or(memory_config.and_then(|c| c.target_lang.to_owned())),
+    )
+}
+
+/// Records translations that were made without a translator.
+pub fn record_translations(
+    config: &Config,
+    pair: &LanguagePair,
+    translations: &[Translation],
+) -> Result<(), anyhow::Error> {
+    if let Some(mut memory) = load_translation_memory(config)? {
+        memory.record(pair, translations);
+        memory.save()?;
+    }
+
+    Ok(())
+}
+
+/// Loads the translation memory or None if it has been disabled.
+pub fn load_translation_memory(config: &Config) -> Result<Option<TranslationMemory>, anyhow::Error> {
+    let memory_config = config.translation_memory.as_ref();
+
+    if memory_config.and_then(|c| c.enabled) == Some(false) {
+        return Ok(None);
+    }
+
+    let path = match memory_config.and_then(|c| c.path.to_owned()) {
+        Some(path) => path,
+        None => TranslationMemory::default_path()?,
+    };
+
+    Ok(Some(TranslationMemory::load(&path)?))
+}
+
+/// Loads the glossary of the language pair or None if it has been disabled or there is
+/// no glossary file for the pair.
+pub fn load_glossary(config: &Config, pair: &LanguagePair) -> Result<Option<Glossary>, anyhow::Error> {
+    let glossary_config = config.glossary.as_ref();
+
+    if glossary_config.and_then(|c| c.enabled) == Some(false) {
+        return Ok(None);
+    }
+
+    let dir = glossary_config
+        .and_then(|c| c.dir.to_owned())
+        .unwrap_or(PathBuf::from(GLOSSARY_DIR));
+
+    let path = Glossary::path(&dir, pair);
+    let glossary = Glossary::load(&path)?;
+
+    if glossary.is_some() {
+        print_bracketed_info("Using glossary", &path.to_string_lossy());
+    }
+
+    Ok(glossary)
+}
+
+/// Applies the glossary to the translations and reports the lines that still do not
+/// render a glossary term as expected.
+pub fn enforce_glossary(glossary: &Glossary, translations: &mut [Translation]) {
+    let changed = glossary.apply(translations);
+
+    if changed > 0 {
+        print_bracketed_info("Glossary terms corrected in lines", &changed.to_string());
+    }
+
+    let mismatches = glossary.check(translations);
+
+    if !mismatches.is_empty() {
+        print_error(&format!(
+            "{} glossary terms were not translated as expected:",
+            mismatches.len()
+        ));
+        println!("{}", Table::new(mismatches).with(Style::markdown()));
+    }
+}
+
+fn build_backend(
+    kind: TranslatorKind,
+    config: &Config,
+    prompt: &str,
+) -> Result<Box<dyn Translator>, anyhow::Error> {
+    match kind {
+        TranslatorKind::Clipboard => Ok(Box::new(ClipboardTranslator::new(prompt))),
+        TranslatorKind::Editor => Ok(Box::new(EditorTranslator)),
+        TranslatorKind::Deepl => {
+            let deepl = config.deepl.clone().ok_or(missing_section("deepl"))?;
+            Ok(Box::new(DeeplTranslator::new(deepl)?))
+        }
+        TranslatorKind::LibreTranslate => {
+            let libre_translate = config
+                .libre_translate
+                .clone()
+                .ok_or(missing_section("libre_translate"))?;
+            Ok(Box::new(LibreTranslateTranslator::new(libre_translate)?))
+        }
+        TranslatorKind::OpenAi => {
+            let openai = config.openai.clone().ok_or(missing_section("openai"))?;
+            Ok(Box::new(OpenAiTranslator::new(openai)?))
+        }
+        TranslatorKind::ExternalCommand => {
+            let external_command = config
+                .external_command
+                .clone()
+                .ok_or(missing_section("external_command"))?;
+            Ok(Box::new(ExternalCommandTranslator::new(external_command)))
+        }
+    }
+}
+
+fn print_chunk_progress(chunk: usize, chunks: usize) {
+    if chunks > 1 {
+        print_bracketed_info("Translating chunk", &format!("{} of {}", chunk, chunks));
+    }
+}
+
+fn missing_section(section: &str) -> anyhow::Error {
+    anyhow!(
+        "The {} translator requires a <{}> section in ~/.config/lang-tools/config.yaml",
+        section,
+        section
+    )
+}
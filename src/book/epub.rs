@@ -25,7 +25,7 @@ impl Chapter {
                     Chapter::new(
                         book_title.to_owned(),
                         x.0.to_owned(),
-                        html2text::from_read(content.as_bytes(), std::usize::MAX),
+                        html2text::from_read(content.as_bytes(), usize::MAX),
                         base_path.to_owned())
                 })
             })
@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use itertools::Itertools;
 
@@ -29,16 +29,16 @@ impl Chapter {
     }
 }
 
-pub fn book_path(base_path: &PathBuf, book_title: &str) -> PathBuf {
+pub fn book_path(base_path: &Path, book_title: &str) -> PathBuf {
     return base_path.join(book_title);
 }
 
-pub fn toc_path(book_path: &PathBuf, book_title: &str) -> PathBuf {
+pub fn toc_path(book_path: &Path, book_title: &str) -> PathBuf {
     book_path.join(format!("{}.md", book_title))
 }
 
 // If there are chapters with a file path that exists then write the TOC.
-pub fn write_toc(path: &PathBuf, book_title: &str, chapters: &[Chapter]) -> Result<(), anyhow::Error> {
+pub fn write_toc(path: &Path, book_title: &str, chapters: &[Chapter]) -> Result<(), anyhow::Error> {
     let toc_chapters = chapters
         .iter()
         .filter(|c| c.path.exists())
@@ -51,7 +51,7 @@ pub fn write_toc(path: &PathBuf, book_title: &str, chapters: &[Chapter]) -> Resu
         })
         .join("\n");
 
-    if toc_chapters.len() > 0 {
+    if !toc_chapters.is_empty() {
         let toc = format!("# {}\n{}", book_title, toc_chapters);
 
         write_to_file(path, &toc)
@@ -0,0 +1,120 @@
+/// Closing quotes and brackets that may follow the punctuation ending a clause.
+const CLOSING: [char; 9] = ['"', '\'', ')', ']', '»', '«', '“', '”', '」'];
+
+/// The clause punctuation a unit ends with, normalized so that full width and ASCII
+/// punctuation compare equal.
+fn clause_punctuation(unit: &str) -> Option<char> {
+    if matches!(unit, "-" | "–" | "—") {
+        return Some('-');
+    }
+
+    match unit.trim_end_matches(CLOSING).chars().last()? {
+        ',' | '，' | '、' => Some(','),
+        ':' | '：' => Some(':'),
+        ';' | '；' => Some(';'),
+        '?' | '？' => Some('?'),
+        '!' | '！' => Some('!'),
+        '–' | '—' => Some('-'),
+        _ => None,
+    }
+}
+
+/// The positions after which a clause ends together with its punctuation. The end of the
+/// text is not a clause boundary.
+fn boundaries(units: &[&str]) -> Vec<(usize, char)> {
+    units
+        .iter()
+        .enumerate()
+        .take(units.len().saturating_sub(1))
+        .filter_map(|(index, unit)| clause_punctuation(unit).map(|p| (index + 1, p)))
+        .collect()
+}
+
+/// Splits the source words and the target units at their clause punctuation when both
+/// have the same punctuation in the same order. Otherwise a single pair holding all words
+/// is returned.
+pub fn clauses<'a>(source: Vec<&'a str>, target: Vec<&'a str>) -> Vec<(Vec<&'a str>, Vec<&'a str>)> {
+    let source_boundaries = boundaries(&source);
+    let target_boundaries = boundaries(&target);
+
+    let matching = !source_boundaries.is_empty()
+        && source_boundaries.len() == target_boundaries.len()
+        && source_boundaries
+            .iter()
+            .zip(target_boundaries.iter())
+            .all(|((_, s), (_, t))| s == t);
+
+    if !matching {
+        return vec![(source, target)];
+    }
+
+    let mut pairs = Vec::new();
+    let mut source_start = 0;
+    let mut target_start = 0;
+
+    for ((source_end, _), (target_end, _)) in source_boundaries.into_iter().zip(target_boundaries) {
+        pairs.push((
+            source[source_start..source_end].to_vec(),
+            target[target_start..target_end].to_vec(),
+        ));
+        source_start = source_end;
+        target_start = target_end;
+    }
+
+    pairs.push((source[source_start..].to_vec(), target[target_start..].to_vec()));
+
+    pairs
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn words(text: &str) -> Vec<&str> {
+        text.split_whitespace().collect()
+    }
+
+    #[test]
+    fn splits_at_matching_punctuation() {
+        let pairs = clauses(
+            words("Es geht um Mountainbiker und die Frage: Wer darf hier wie den Wald nutzen?"),
+            words("It's about mountain bikers and the question: Who is allowed to use the forest here and how?"),
+        );
+
+        assert_eq!(
+            pairs,
+            vec![
+                (
+                    words("Es geht um Mountainbiker und die Frage:"),
+                    words("It's about mountain bikers and the question:")
+                ),
+                (
+                    words("Wer darf hier wie den Wald nutzen?"),
+                    words("Who is allowed to use the forest here and how?")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_whole_text_when_punctuation_differs() {
+        let source = words("Ich weiß, dass es regnet: leider.");
+        let target = words("I know that it is raining: unfortunately.");
+
+        assert_eq!(clauses(source.clone(), target.clone()), vec![(source, target)]);
+    }
+
+    #[test]
+    fn matches_full_width_punctuation() {
+        let source = words("Ja, wirklich?");
+        let target = vec!["は", "い", "、", "本", "当", "？"];
+
+        assert_eq!(
+            clauses(source, target),
+            vec![
+                (vec!["Ja,"], vec!["は", "い", "、"]),
+                (vec!["wirklich?"], vec!["本", "当", "？"]),
+            ]
+        );
+    }
+}
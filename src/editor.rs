@@ -0,0 +1,142 @@
+use fancy_regex::Regex;
+use itertools::Itertools;
+use lazy_static::lazy_static;
+
+lazy_static! {
+    // The comment holding a source line. For example "# [12] Wie geht's?"
+    static ref SOURCE_MARKER_RE: Regex = Regex::new(r"^#\s*\[(?P<num>\d+)\]").unwrap();
+}
+
+/// The prefix of the comment lines written to the editor. Only lines starting with it, or
+/// made of a lone '#', are ignored so translations such as "#1 hit" are kept.
+const COMMENT_PREFIX: &str = "# ";
+
+const INSTRUCTIONS: &str = "\
+# Write the translation of each source line on the empty line below it.
+# Lines starting with \"# \" are ignored. Save and close the editor when done.";
+
+/// Builds the text shown in the editor. Each non empty source line is written as a
+/// numbered comment followed by its translation which is empty on the first edit. The
+/// numbers of lines that were left untranslated in an earlier edit are listed at the top.
+pub fn to_editor_text(source: &[&str], target: &[String], missing: &[usize]) -> String {
+    let mut text = String::from(INSTRUCTIONS);
+
+    if !missing.is_empty() {
+        text.push_str(&format!(
+            "\n#\n# Missing translations for lines: {}",
+            missing.iter().map(|i| format!("[{}]", i + 1)).join(", ")
+        ));
+    }
+
+    for (index, line) in source.iter().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        text.push_str(&format!(
+            "\n\n# [{}] {}\n{}",
+            index + 1,
+            line,
+            target.get(index).map_or("", |t| t.as_str())
+        ));
+    }
+
+    text.push('\n');
+
+    return text;
+}
+
+/// Reads the translations back from the edited text. The lines following a source comment
+/// up to the next source comment are joined into its translation. Returns one translation
+/// per source line which is empty if none was found.
+pub fn parse_editor_text(text: &str, source_len: usize) -> Vec<String> {
+    let mut target: Vec<String> = vec![String::new(); source_len];
+    let mut current: Option<usize> = None;
+
+    for line in text.lines() {
+        if let Ok(Some(captures)) = SOURCE_MARKER_RE.captures(line) {
+            current = captures["num"]
+                .parse::<usize>()
+                .ok()
+                .filter(|num| (1..=source_len).contains(num))
+                .map(|num| num - 1);
+        } else if is_comment(line) || line.trim().is_empty() {
+            continue;
+        } else if let Some(index) = current {
+            let translation = &mut target[index];
+
+            if !translation.is_empty() {
+                translation.push(' ');
+            }
+
+            translation.push_str(line.trim());
+        }
+    }
+
+    return target;
+}
+
+fn is_comment(line: &str) -> bool {
+    line.starts_with(COMMENT_PREFIX) || line.trim_end() == "#"
+}
+
+/// The indexes of the non empty source lines without a translation.
+pub fn missing_translations(source: &[&str], target: &[String]) -> Vec<usize> {
+    source
+        .iter()
+        .enumerate()
+        .filter(|(index, line)| {
+            !line.trim().is_empty() && target.get(*index).is_none_or(|t| t.trim().is_empty())
+        })
+        .map(|(index, _)| index)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn source_lines_are_numbered_comments() {
+        let text = to_editor_text(&["Eins.", "", "Zwei."], &[], &[]);
+
+        assert_eq!(text, format!("{}\n\n# [1] Eins.\n\n\n# [3] Zwei.\n\n", INSTRUCTIONS));
+    }
+
+    #[test]
+    fn earlier_translations_and_missing_lines_are_kept() {
+        let text = to_editor_text(
+            &["Eins.", "Zwei."],
+            &["One.".to_owned(), "".to_owned()],
+            &[1],
+        );
+
+        assert!(text.contains("# Missing translations for lines: [2]"));
+        assert!(text.contains("# [1] Eins.\nOne.\n"));
+    }
+
+    #[test]
+    fn translations_are_read_below_their_source() {
+        let text = "# header\n\n# [1] Eins.\nOne.\n\n# [2] Zwei drei.\nTwo\nthree.\n# [3] Vier.\n";
+
+        let target = parse_editor_text(text, 3);
+
+        assert_eq!(target, vec!["One.", "Two three.", ""]);
+    }
+
+    #[test]
+    fn translations_starting_with_hash_are_kept() {
+        let text = format!("{}\n#\n\n# [1] Nummer eins.\n#1 hit.\n# [2] Zwei.\n#zwei\n", INSTRUCTIONS);
+
+        let target = parse_editor_text(&text, 2);
+
+        assert_eq!(target, vec!["#1 hit.", "#zwei"]);
+    }
+
+    #[test]
+    fn missing_translations_ignore_empty_source_lines() {
+        let target = vec!["One.".to_owned(), "".to_owned(), "".to_owned()];
+
+        assert_eq!(missing_translations(&["Eins.", "", "Drei."], &target), vec![2]);
+    }
+}
@@ -1,4 +1,4 @@
-use std::{path::PathBuf, fs::{self, File}, io::Write};
+use std::{path::{Path, PathBuf}, fs::{self, File}, io::Write};
 
 use anyhow::Context;
 
@@ -9,8 +9,8 @@ pub fn read_from_file(file: PathBuf) -> Result<String, anyhow::Error> {
     return fs::read_to_string(&file).context(format!("Failed reading from file {:?}.", file))
 }
 
-pub fn write_to_file(file: &PathBuf, text: &str) -> Result<(), anyhow::Error> {
-    let file = expand_path(&file)?;
+pub fn write_to_file(file: &Path, text: &str) -> Result<(), anyhow::Error> {
+    let file = expand_path(file)?;
 
     if let Some(parent) = file.parent() {
         std::fs::create_dir_all(parent)
@@ -0,0 +1,272 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use fancy_regex::{escape, Captures, Regex};
+use serde::{Deserialize, Serialize};
+use tabled::Tabled;
+
+use crate::{memory::LanguagePair, path::expand_path, translation::Translation};
+
+/// A term that has to be rendered the same way in every translation.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct GlossaryEntry {
+    /// The term as it appears in the source text.
+    pub source: String,
+
+    /// How the term is rendered in the target text. The term is kept as is when missing.
+    pub target: Option<String>,
+
+    /// Renderings that are replaced with the expected one.
+    #[serde(default)]
+    pub variants: Vec<String>,
+}
+
+impl GlossaryEntry {
+    /// The text the target line is expected to contain for the term.
+    pub fn expected(&self) -> &str {
+        self.target.as_deref().unwrap_or(&self.source)
+    }
+}
+
+/// A target line that does not render a glossary term as expected.
+#[derive(Debug, Clone, PartialEq, Tabled)]
+pub struct GlossaryMismatch {
+    #[tabled(rename = "Line", display_with = "display_line")]
+    pub line: usize,
+
+    #[tabled(rename = "Term")]
+    pub term: String,
+
+    #[tabled(rename = "Expected")]
+    pub expected: String,
+
+    #[tabled(rename = "Translated")]
+    pub target_text: String,
+}
+
+fn display_line(line: &usize) -> String {
+    format!("{}", line + 1)
+}
+
+struct Term {
+    entry: GlossaryEntry,
+    source: Regex,
+    expected: Regex,
+    replace: Option<Regex>,
+}
+
+/// The terminology of a language pair.
+pub struct Glossary {
+    terms: Vec<Term>,
+}
+
+impl Glossary {
+    pub fn new(entries: Vec<GlossaryEntry>) -> Result<Self, anyhow::Error> {
+        let terms = entries
+            .into_iter()
+            .map(Term::new)
+            .collect::<Result<Vec<Term>, anyhow::Error>>()?;
+
+        Ok(Glossary { terms })
+    }
+
+    /// The file holding the glossary of the language pair in dir.
+    pub fn path(dir: &Path, pair: &LanguagePair) -> PathBuf {
+        dir.join(format!("{}-{}.yaml", pair.source, pair.target))
+    }
+
+    /// Loads the YAML list of entries at path or None if there is no such file.
+    pub fn load(path: &Path) -> Result<Option<Self>, anyhow::Error> {
+        let path = expand_path(path)?;
+
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let content = fs::read_to_string(&path)
+            .context(format!("Failed to read glossary [{:?}]", path))?;
+        let entries: Vec<GlossaryEntry> = serde_yaml::from_str(&content)
+            .context(format!("Failed to parse glossary [{:?}]", path))?;
+
+        Ok(Some(Glossary::new(entries)?))
+    }
+
+    /// Replaces the variants of each term found in the source with the expected rendering.
+    /// A term left untranslated is replaced as well. Returns the number of changed lines.
+    pub fn apply(&self, translations: &mut [Translation]) -> usize {
+        let mut changed = 0;
+
+        for translation in translations.iter_mut() {
+            let mut target_text = translation.target_text.to_owned();
+
+            for term in self.terms.iter().filter(|t| t.in_source(&translation.source_text)) {
+                target_text = term.replace(&target_text);
+            }
+
+            if target_text != translation.target_text {
+                translation.target_text = target_text;
+                changed += 1;
+            }
+        }
+
+        changed
+    }
+
+    /// Returns the lines that contain a term in the source but not its expected rendering.
+    pub fn check(&self, translations: &[Translation]) -> Vec<GlossaryMismatch> {
+        let mut mismatches = Vec::new();
+
+        for (line, translation) in translations.iter().enumerate() {
+            for term in self.terms.iter() {
+                if term.in_source(&translation.source_text)
+                    && !term.in_target(&translation.target_text)
+                {
+                    mismatches.push(GlossaryMismatch {
+                        line,
+                        term: term.entry.source.to_owned(),
+                        expected: term.entry.expected().to_owned(),
+                        target_text: translation.target_text.to_owned(),
+                    });
+                }
+            }
+        }
+
+        mismatches
+    }
+}
+
+impl Term {
+    fn new(entry: GlossaryEntry) -> Result<Self, anyhow::Error> {
+        let source = Regex::new(&format!(r"(?<!\w){}(?!\w)", escape(&entry.source)))?;
+        let expected = Regex::new(&format!(r"(?i)(?<!\w){}", escape(entry.expected())))?;
+
+        let mut replaced: Vec<String> = entry
+            .variants
+            .iter()
+            .filter(|v| !v.trim().is_empty())
+            .map(|v| escape(v).into_owned())
+            .collect();
+
+        if entry.source != entry.expected() {
+            replaced.push(escape(&entry.source).into_owned());
+        }
+
+        // Longer variants first so that a variant containing another one is replaced whole.
+        replaced.sort_by_key(|v| std::cmp::Reverse(v.len()));
+
+        let replace = match replaced.is_empty() {
+            true => None,
+            false => Some(Regex::new(&format!(r"(?i)(?<!\w)(?:{})(?!\w)", replaced.join("|")))?),
+        };
+
+        Ok(Term { entry, source, expected, replace })
+    }
+
+    fn in_source(&self, text: &str) -> bool {
+        self.source.is_match(text).unwrap_or(false)
+    }
+
+    fn in_target(&self, text: &str) -> bool {
+        self.expected.is_match(text).unwrap_or(false)
+    }
+
+    fn replace(&self, text: &str) -> String {
+        let expected = self.entry.expected();
+
+        match &self.replace {
+            Some(replace) => replace
+                .replace_all(text, |caps: &Captures| match_case(&caps[0], expected))
+                .into_owned(),
+            None => text.to_owned(),
+        }
+    }
+}
+
+/// Capitalizes the replacement when the replaced text starts a sentence with a capital
+/// letter but the expected rendering does not.
+fn match_case(replaced: &str, expected: &str) -> String {
+    let replaced_upper = replaced.chars().next().is_some_and(|c| c.is_uppercase());
+    let mut chars = expected.chars();
+
+    match chars.next() {
+        Some(first) if replaced_upper && first.is_lowercase() => {
+            first.to_uppercase().chain(chars).collect()
+        }
+        _ => expected.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn glossary() -> Glossary {
+        Glossary::new(vec![
+            GlossaryEntry {
+                source: "Y-Kollektiv".to_owned(),
+                target: None,
+                variants: vec!["Y Collective".to_owned()],
+            },
+            GlossaryEntry {
+                source: "Mountainbiker".to_owned(),
+                target: Some("mountain biker".to_owned()),
+                variants: vec!["mountainbiker".to_owned(), "mountain-biker".to_owned()],
+            },
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn applies_variants_and_untranslated_terms() {
+        let mut translations = vec![
+            Translation::new("Das Y-Kollektiv war dort.", "The Y Collective was there."),
+            Translation::new("Mountainbiker lieben das.", "Mountainbikers love it."),
+            Translation::new("Ein Mountainbiker fährt.", "A mountain-biker rides."),
+            Translation::new("Nichts davon.", "Y Collective is not in the source."),
+        ];
+
+        assert_eq!(glossary().apply(&mut translations), 2);
+        assert_eq!(translations[0].target_text, "The Y-Kollektiv was there.");
+        assert_eq!(translations[1].target_text, "Mountainbikers love it.");
+        assert_eq!(translations[2].target_text, "A mountain biker rides.");
+        assert_eq!(translations[3].target_text, "Y Collective is not in the source.");
+    }
+
+    #[test]
+    fn capitalizes_replacement_at_sentence_start() {
+        let mut translations = vec![Translation::new("Mountainbiker sind da.", "Mountain-biker are here.")];
+
+        glossary().apply(&mut translations);
+
+        assert_eq!(translations[0].target_text, "Mountain biker are here.");
+    }
+
+    #[test]
+    fn reports_terms_not_rendered_as_expected() {
+        let translations = vec![
+            Translation::new("Das Y-Kollektiv war dort.", "The Y-Kollektiv was there."),
+            Translation::new("Mountainbiker lieben das.", "Cyclists love it."),
+            Translation::new("Ein Mountainbiker fährt.", "Mountain bikers ride."),
+        ];
+
+        assert_eq!(
+            glossary().check(&translations),
+            vec![GlossaryMismatch {
+                line: 1,
+                term: "Mountainbiker".to_owned(),
+                expected: "mountain biker".to_owned(),
+                target_text: "Cyclists love it.".to_owned(),
+            }]
+        );
+    }
+
+    #[test]
+    fn matches_whole_source_terms_only() {
+        let translations = vec![Translation::new("Die Mountainbikerin fährt.", "The cyclist rides.")];
+
+        assert!(glossary().check(&translations).is_empty());
+    }
+}
@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+
+use log::trace;
+
+use crate::{segmenter::Segmenter, tokenizer::Tokenizer, translation::Translation};
+
+/// The number of expectation maximization rounds used to learn the probabilities.
+pub const TRAINING_ITERATIONS: usize = 5;
+
+/// Target words whose most likely source word has a lower probability are not used to
+/// place breaks.
+const MIN_PROBABILITY: f64 = 0.3;
+
+/// The share of the target words of a translation that must be confidently aligned for the
+/// lexicon to be used rather than proportional distribution.
+const MIN_CONFIDENT_SHARE: f64 = 0.5;
+
+/// Stands for the source word of target words that have no counterpart in the source.
+const NULL_WORD: &str = "";
+
+/// Word translation probabilities learned from translations in the style of IBM Model 1.
+#[derive(Debug, Default)]
+pub struct Lexicon {
+    /// The probability of a target word given a source word keyed by source word.
+    probabilities: HashMap<String, HashMap<String, f64>>,
+}
+
+impl Lexicon {
+    /// Learns the probabilities from the translations. The source text is split with the
+    /// tokenizer and the target text with the segmenter so that the words match the ones
+    /// being aligned later.
+    pub fn train(
+        translations: &[Translation],
+        tokenizer: &dyn Tokenizer,
+        segmenter: &dyn Segmenter,
+        iterations: usize,
+    ) -> Self {
+        let pairs: Vec<(Vec<String>, Vec<String>)> = translations
+            .iter()
+            .map(|t| {
+                let mut source = words(tokenizer.words(&t.source_text).into_iter());
+                source.push(NULL_WORD.to_owned());
+                (source, words(segmenter.segment(&t.target_text).into_iter()))
+            })
+            .filter(|(_, target)| !target.is_empty())
+            .collect();
+
+        let mut lexicon = Lexicon::default();
+
+        // Every target word seen with a source word starts out equally likely.
+        for (source, target) in pairs.iter() {
+            for s in source.iter() {
+                let row = lexicon.probabilities.entry(s.to_owned()).or_default();
+                for t in target.iter() {
+                    row.insert(t.to_owned(), 1.0);
+                }
+            }
+        }
+
+        for iteration in 0..iterations {
+            let mut counts: HashMap<&str, HashMap<&str, f64>> = HashMap::new();
+
+            for (source, target) in pairs.iter() {
+                for t in target.iter() {
+                    let total: f64 = source.iter().map(|s| lexicon.raw(s, t)).sum();
+
+                    if total == 0.0 {
+                        continue;
+                    }
+
+                    for s in source.iter() {
+                        *counts.entry(s).or_default().entry(t).or_default() +=
+                            lexicon.raw(s, t) / total;
+                    }
+                }
+            }
+
+            for (s, row) in counts {
+                let total: f64 = row.values().sum();
+                let probabilities = lexicon.probabilities.entry(s.to_owned()).or_default();
+
+                for (t, count) in row {
+                    probabilities.insert(t.to_owned(), count / total);
+                }
+            }
+
+            trace!("Finished lexicon training iteration {}", iteration + 1);
+        }
+
+        lexicon
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.probabilities.is_empty()
+    }
+
+    /// The probability that the target word translates the source word.
+    pub fn probability(&self, source: &str, target: &str) -> f64 {
+        self.raw(&normalize(source), &normalize(target))
+    }
+
+    fn raw(&self, source: &str, target: &str) -> f64 {
+        self.probabilities
+            .get(source)
+            .and_then(|row| row.get(target))
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    /// The number of target units given to each source word so that the translated words
+    /// of a source word end up next to it. None when too few target words are known well
+    /// enough to rely on.
+    pub fn counts(&self, source: &[&str], target: &[&str]) -> Option<Vec<i128>> {
+        if source.is_empty() || target.is_empty() {
+            return None;
+        }
+
+        let source_words: Vec<String> = source.iter().map(|s| normalize(s)).collect();
+
+        // The source position each target word most likely translates if it is known well.
+        let aligned: Vec<Option<usize>> = target
+            .iter()
+            .map(|t| {
+                let t = normalize(t);
+
+                source_words
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, s)| !s.is_empty())
+                    .map(|(i, s)| (i, self.raw(s, &t)))
+                    .filter(|(_, p)| *p >= MIN_PROBABILITY && *p > self.raw(NULL_WORD, &t))
+                    .max_by(|a, b| a.1.total_cmp(&b.1))
+                    .map(|(i, _)| i)
+            })
+            .collect();
+
+        let confident = aligned.iter().flatten().count();
+
+        if (confident as f64) < MIN_CONFIDENT_SHARE * target.len() as f64 {
+            trace!("Only {} of {} target words are known. Not using the lexicon", confident, target.len());
+            return None;
+        }
+
+        // The target break after each source word, never before the previous one.
+        let mut breaks = vec![0];
+
+        for k in 1..source.len() {
+            let previous = *breaks.last().unwrap_or(&0);
+            let proportional = (k * target.len() + source.len() / 2) / source.len();
+
+            let best = (previous..=target.len())
+                .min_by_key(|j| (crossings(&aligned, k, *j), j.abs_diff(proportional)))
+                .unwrap_or(previous);
+
+            breaks.push(best);
+        }
+
+        breaks.push(target.len());
+
+        Some(breaks.windows(2).map(|w| (w[1] - w[0]) as i128).collect())
+    }
+}
+
+/// The number of known target words that end up on the wrong side of a break at target
+/// position j for a break after source position k.
+fn crossings(aligned: &[Option<usize>], k: usize, j: usize) -> usize {
+    let before = aligned[..j].iter().flatten().filter(|i| **i >= k).count();
+    let after = aligned[j..].iter().flatten().filter(|i| **i < k).count();
+    before + after
+}
+
+fn words<'a, I: Iterator<Item = &'a str>>(words: I) -> Vec<String> {
+    words.map(normalize).filter(|w| !w.is_empty()).collect()
+}
+
+/// Lower cases the word and removes the punctuation around it.
+fn normalize(word: &str) -> String {
+    word.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{segmenter::WhitespaceSegmenter, tokenizer::UnicodeTokenizer};
+
+    fn corpus() -> Vec<Translation> {
+        vec![
+            Translation::new("Ich habe das Buch gelesen.", "I have read the book."),
+            Translation::new("Ich habe das Buch.", "I have the book."),
+            Translation::new("Du hast das Buch gelesen.", "You have read the book."),
+            Translation::new("Das Buch ist gut.", "The book is good."),
+            Translation::new("Ich habe gelesen.", "I have read."),
+            Translation::new("Du hast ein Haus.", "You have a house."),
+            Translation::new("Das Haus ist gut.", "The house is good."),
+            Translation::new("Ich habe ein Haus gesehen.", "I have seen a house."),
+            Translation::new("Du hast das Haus gesehen.", "You have seen the house."),
+        ]
+    }
+
+    #[test]
+    fn learns_word_translations() {
+        let lexicon = Lexicon::train(&corpus(), &UnicodeTokenizer, &WhitespaceSegmenter, TRAINING_ITERATIONS);
+
+        assert!(lexicon.probability("Buch", "book") > 0.5);
+        assert!(lexicon.probability("gelesen", "read.") > 0.5);
+        assert!(lexicon.probability("Buch", "house") < 0.1);
+    }
+
+    #[test]
+    fn breaks_follow_verb_final_word_order() {
+        let lexicon = Lexicon::train(&corpus(), &UnicodeTokenizer, &WhitespaceSegmenter, TRAINING_ITERATIONS);
+
+        let source = ["Du", "hast", "das", "Buch", "gesehen."];
+        let target = ["You", "have", "seen", "the", "book."];
+
+        let counts = lexicon.counts(&source, &target).unwrap();
+
+        // A break after "hast" keeps "You have" together.
+        assert_eq!(counts[..2].iter().sum::<i128>(), 2);
+
+        // "gesehen" is translated by "seen" which comes before "the book". A break after
+        // "Buch" therefore has to come after "book".
+        assert_eq!(counts[..4].iter().sum::<i128>(), 5);
+    }
+
+    #[test]
+    fn falls_back_without_known_words() {
+        let lexicon = Lexicon::train(&corpus(), &UnicodeTokenizer, &WhitespaceSegmenter, TRAINING_ITERATIONS);
+
+        assert_eq!(lexicon.counts(&["Guten", "Morgen"], &["Good", "morning"]), None);
+    }
+}
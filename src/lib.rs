@@ -1,8 +1,18 @@
+#![allow(clippy::needless_return)]
+
 pub mod book;
+pub mod clause;
 pub mod clipboard;
+pub mod editor;
 pub mod file;
+pub mod glossary;
+pub mod lexicon;
+pub mod memory;
 pub mod path;
 pub mod proportional;
+pub mod segmenter;
 pub mod subtitle;
+pub mod tokenizer;
 pub mod translation;
+pub mod translator;
 pub mod youtube;
\ No newline at end of file
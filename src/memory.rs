@@ -0,0 +1,265 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{anyhow, Context};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use tabled::Tabled;
+
+use crate::{
+    file::{read_from_file, write_to_file},
+    path::expand_path,
+    translation::{Translation, Translator},
+};
+
+/// The language used for either side of a pair when it is not known.
+pub const UNDETERMINED_LANG: &str = "und";
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LanguagePair {
+    pub source: String,
+    pub target: String,
+}
+
+impl LanguagePair {
+    pub fn new(source: Option<String>, target: Option<String>) -> Self {
+        LanguagePair {
+            source: source.unwrap_or(UNDETERMINED_LANG.to_owned()),
+            target: target.unwrap_or(UNDETERMINED_LANG.to_owned()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Tabled)]
+pub struct MemoryEntry {
+    #[tabled(rename = "From")]
+    pub source_lang: String,
+
+    #[tabled(rename = "To")]
+    pub target_lang: String,
+
+    #[tabled(rename = "Source")]
+    pub source_text: String,
+
+    #[tabled(rename = "Translated")]
+    pub target_text: String,
+
+    /// How many times the entry has been looked up.
+    #[tabled(rename = "Hits")]
+    pub hits: u64,
+
+    /// Seconds since the unix epoch when the entry was last recorded or looked up.
+    #[tabled(skip)]
+    pub last_used: u64,
+}
+
+type MemoryKey = (String, String, String);
+
+/// Every translation made so far keyed by the language pair and the normalized source
+/// sentence. The memory is stored as a JSON file.
+#[derive(Debug)]
+pub struct TranslationMemory {
+    path: PathBuf,
+    entries: BTreeMap<MemoryKey, MemoryEntry>,
+}
+
+impl TranslationMemory {
+    /// ~/.local/share/lang-tools/translation_memory.json on Linux.
+    pub fn default_path() -> Result<PathBuf, anyhow::Error> {
+        dirs::data_local_dir()
+            .map(|dir| dir.join("lang-tools").join("translation_memory.json"))
+            .ok_or(anyhow!("Unable to determine the local data directory."))
+    }
+
+    /// Loads the memory at path. A missing file is treated as an empty memory.
+    pub fn load(path: &Path) -> Result<Self, anyhow::Error> {
+        let path = expand_path(path)?;
+
+        let entries: Vec<MemoryEntry> = if path.exists() {
+            serde_json::from_str(&read_from_file(path.to_owned())?)
+                .context(format!("Failed to parse translation memory {:?}.", path))?
+        } else {
+            Vec::new()
+        };
+
+        let entries = entries
+            .into_iter()
+            .map(|e| (key(&e.source_lang, &e.target_lang, &e.source_text), e))
+            .collect();
+
+        return Ok(TranslationMemory { path, entries });
+    }
+
+    pub fn save(&self) -> Result<(), anyhow::Error> {
+        let entries: Vec<&MemoryEntry> = self.entries.values().collect();
+        let json = serde_json::to_string_pretty(&entries)?;
+
+        write_to_file(&self.path, &json)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &MemoryEntry> {
+        self.entries.values()
+    }
+
+    /// Returns the recorded translations of the language pair.
+    pub fn translations(&self, pair: &LanguagePair) -> Vec<Translation> {
+        self.entries
+            .values()
+            .filter(|e| e.source_lang == pair.source && e.target_lang == pair.target)
+            .map(|e| Translation::new(&e.source_text, &e.target_text))
+            .collect()
+    }
+
+    /// Returns the translation of source if it has been seen before.
+    pub fn lookup(&mut self, pair: &LanguagePair, source: &str) -> Option<String> {
+        self.entries
+            .get_mut(&key(&pair.source, &pair.target, source))
+            .map(|entry| {
+                entry.hits += 1;
+                entry.last_used = now();
+                entry.target_text.to_owned()
+            })
+    }
+
+    /// Records the translations replacing any earlier translation of the same source.
+    pub fn record(&mut self, pair: &LanguagePair, translations: &[Translation]) {
+        for translation in translations {
+            let source_text = normalize(&translation.source_text);
+
+            if source_text.is_empty() {
+                continue;
+            }
+
+            let entry = self
+                .entries
+                .entry(key(&pair.source, &pair.target, &source_text))
+                .or_insert(MemoryEntry {
+                    source_lang: pair.source.to_owned(),
+                    target_lang: pair.target.to_owned(),
+                    source_text,
+                    target_text: String::new(),
+                    hits: 0,
+                    last_used: 0,
+                });
+
+            entry.target_text = translation.target_text.trim().to_owned();
+            entry.last_used = now();
+        }
+    }
+
+    /// Entries where the source or target contains the query ignoring case.
+    pub fn search(&self, query: &str) -> Vec<&MemoryEntry> {
+        let query = query.to_lowercase();
+
+        self.entries
+            .values()
+            .filter(|e| {
+                e.source_text.to_lowercase().contains(&query)
+                    || e.target_text.to_lowercase().contains(&query)
+            })
+            .collect()
+    }
+
+    /// Removes every entry matching the predicate and returns how many were removed.
+    pub fn prune<P: Fn(&MemoryEntry) -> bool>(&mut self, predicate: P) -> usize {
+        let before = self.entries.len();
+        self.entries.retain(|_, e| !predicate(e));
+        return before - self.entries.len();
+    }
+}
+
+/// Looks up each source line in the translation memory and only sends the lines it has
+/// not seen before to the wrapped translator. New translations are recorded and the memory
+/// saved right away so they survive a failure later in the command.
+pub struct MemoryTranslator {
+    memory: TranslationMemory,
+    pair: LanguagePair,
+    translator: Box<dyn Translator>,
+}
+
+impl MemoryTranslator {
+    pub fn new(memory: TranslationMemory, pair: LanguagePair, translator: Box<dyn Translator>) -> Self {
+        MemoryTranslator {
+            memory,
+            pair,
+            translator,
+        }
+    }
+}
+
+impl Translator for MemoryTranslator {
+    fn translate(&mut self, source: &[&str]) -> Result<Vec<String>, anyhow::Error> {
+        let mut translated: Vec<Option<String>> = source
+            .iter()
+            .map(|line| {
+                if line.trim().is_empty() {
+                    Some(line.to_string())
+                } else {
+                    self.memory.lookup(&self.pair, line)
+                }
+            })
+            .collect();
+
+        let missing: Vec<usize> = translated.iter().positions(|t| t.is_none()).collect();
+
+        if !missing.is_empty() {
+            let missing_source: Vec<&str> = missing.iter().map(|&i| source[i]).collect();
+            let missing_target = self.translator.translate(&missing_source)?;
+
+            if missing_target.len() != missing_source.len() {
+                return Err(anyhow!(
+                    "There are {} lines in source and {} lines in target. Number of lines must be of equal length",
+                    missing_source.len(),
+                    missing_target.len()
+                ));
+            }
+
+            let translations: Vec<Translation> = missing_source
+                .iter()
+                .zip(&missing_target)
+                .map(|(s, t)| Translation::new(s, t))
+                .collect();
+
+            self.memory.record(&self.pair, &translations);
+
+            for (index, target) in missing.into_iter().zip(missing_target) {
+                translated[index] = Some(target);
+            }
+        }
+
+        self.memory.save()?;
+
+        Ok(translated.into_iter().map(|t| t.unwrap_or_default()).collect())
+    }
+}
+
+/// Collapses all whitespace so sentences that only differ in spacing share an entry.
+pub fn normalize(source: &str) -> String {
+    source.split_whitespace().join(" ")
+}
+
+fn key(source_lang: &str, target_lang: &str, source: &str) -> MemoryKey {
+    (source_lang.to_owned(), target_lang.to_owned(), normalize(source))
+}
+
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
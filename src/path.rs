@@ -1,12 +1,12 @@
-use std::{path::PathBuf, str::FromStr, env};
+use std::{path::{Path, PathBuf}, str::FromStr, env};
 
 use anyhow::Context;
 
-pub fn expand_path(path: &PathBuf) -> Result<PathBuf, anyhow::Error> {
+pub fn expand_path(path: &Path) -> Result<PathBuf, anyhow::Error> {
     let orig_path = path.to_string_lossy(); 
     let path = shellexpand::tilde(&orig_path);
     
-    return PathBuf::from_str(&path.into_owned())
+    return PathBuf::from_str(&path)
         .context(format!("Failed to expand path [{:?}]", orig_path))
 }
 
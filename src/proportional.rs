@@ -1,22 +1,94 @@
 use std::cmp::Ordering;
-use std::str::SplitWhitespace;
+use std::collections::VecDeque;
+use std::vec::IntoIter;
 
 use log::trace;
 use rational::Rational;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::segmenter::{Segmenter, WhitespaceSegmenter};
+use crate::tokenizer::{Tokenizer, UnicodeTokenizer};
+
+/// Bookkeeping that went wrong while distributing the target words over the source words.
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum ProportionError {
+    #[error("Expected {expected} target words for the source word [{source_word}] but found {found}")]
+    MissingTargetWords {
+        source_word: String,
+        expected: i128,
+        found: usize,
+    },
+
+    #[error("Source words exhausted yet {remaining} target words remain")]
+    RemainingTargetWords { remaining: usize },
+
+    #[error("Source words exhausted with an overage of {overage}")]
+    Overage { overage: String },
+}
+
+/// How much of the target each source word receives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Weighting {
+    /// Every source word has the same weight.
+    #[default]
+    Uniform,
+
+    /// Source words are weighted by their number of letters and digits.
+    Characters,
+
+    /// Source words are weighted by their number of syllables, estimated from groups of
+    /// vowels.
+    Syllables,
+}
+
+impl Weighting {
+    /// The weight of a source word. Every word weighs at least 1.
+    pub fn weight(&self, word: &str) -> i128 {
+        let weight = match self {
+            Weighting::Uniform => 1,
+            Weighting::Characters => word.chars().filter(|c| c.is_alphanumeric()).count(),
+            Weighting::Syllables => syllables(word),
+        };
+
+        weight.max(1) as i128
+    }
+}
+
+fn syllables(word: &str) -> usize {
+    let mut count = 0;
+    let mut in_vowels = false;
+
+    for c in word.to_lowercase().chars() {
+        let vowel = "aeiouyäöüáéíóúàèìòùâêîôûæøåœ".contains(c);
+
+        if vowel && !in_vowels {
+            count += 1;
+        }
+
+        in_vowels = vowel;
+    }
+
+    count
+}
 
 /// Given two strings we want to iterate over them proportionally.
-/// The source and target string are split on white space.
+/// The source string is split on white space and the target string into the units of a
+/// segmenter, which are words split on white space by default.
 /// Each call of next will return a tuple where the first element is the next word from the
 /// source string and the second element is a Vector of 0 or more words from the target word.
 #[derive(Debug)]
 pub struct ProportionalIter<'a> {
-    source_iter: SplitWhitespace<'a>,
-    target_iter: SplitWhitespace<'a>,
+    source_iter: IntoIter<&'a str>,
+    target_iter: IntoIter<&'a str>,
     floor: i128,
     floor_underage: Rational,
     ceiling: i128,
     ceiling_overage: Rational,
     overage: Rational,
+    weighted_counts: Option<VecDeque<i128>>,
+    error: Option<ProportionError>,
 }
 
 impl<'a> ProportionalIter<'a> {
@@ -24,16 +96,63 @@ impl<'a> ProportionalIter<'a> {
         source: &'a str,
         target: &'a str,
     ) -> Self {
-        let source_iter = source.split_whitespace();
-        let target_iter = target.split_whitespace();
+        ProportionalIter::with_segmenter(source, target, &WhitespaceSegmenter)
+    }
+
+    pub fn with_segmenter(
+        source: &'a str,
+        target: &'a str,
+        segmenter: &dyn Segmenter,
+    ) -> Self {
+        ProportionalIter::from_units(UnicodeTokenizer.words(source), segmenter.segment(target))
+    }
+
+    /// Iterates over source words and target units that have already been split.
+    pub fn from_units(
+        source: Vec<&'a str>,
+        target: Vec<&'a str>,
+    ) -> Self {
+        ProportionalIter::from_weighted_units(source, target, Weighting::Uniform)
+    }
+
+    /// Iterates over source words and target units giving each source word the number of
+    /// target units in counts. The counts must add up to the number of target units.
+    pub fn from_counts(
+        source: Vec<&'a str>,
+        target: Vec<&'a str>,
+        counts: Vec<i128>,
+    ) -> Self {
+        let mut iter = ProportionalIter::from_units(source, target);
+        iter.weighted_counts = Some(counts.into());
+        iter
+    }
+
+    /// Iterates over source words and target units giving each source word a share of the
+    /// target units that is proportional to its weight.
+    pub fn from_weighted_units(
+        source: Vec<&'a str>,
+        target: Vec<&'a str>,
+        weighting: Weighting,
+    ) -> Self {
+        let source_len = source.len();
+        let target_len = target.len();
 
-        let source_len = source_iter.clone().count();
-        let target_len = target_iter.clone().count();
+        let weighted_counts = match weighting {
+            Weighting::Uniform => None,
+            _ => Some(weighted_counts(&source, target_len, weighting)),
+        };
+
+        let source_iter = source.into_iter();
+        let target_iter = target.into_iter();
 
         let diff_len: isize = target_len as isize - source_len as isize;
 
-        let (floor, floor_underage) =
-            Rational::new(diff_len as i128, source_len as i128).mixed_fraction();
+        // Without source words there is nothing to distribute the target words over. They
+        // are left in the target iterator and reported by finish.
+        let (floor, floor_underage) = match source_len {
+            0 => (0, Rational::zero()),
+            _ => Rational::new(diff_len as i128, source_len as i128).mixed_fraction(),
+        };
 
         let (ceiling, ceiling_overage) = match floor_underage {
             // If the floor underage is 0 then we set the ceiling to be the same value as the floor
@@ -66,10 +185,38 @@ impl<'a> ProportionalIter<'a> {
             ceiling,
             ceiling_overage,
             overage: Rational::zero(),
+            weighted_counts,
+            error: None,
+        }
+    }
+
+    /// Checks the bookkeeping once the source words have been exhausted. Target words that
+    /// were never returned or words that were missing are reported as an error.
+    pub fn finish(&self) -> Result<(), ProportionError> {
+        if let Some(error) = &self.error {
+            return Err(error.clone());
         }
+
+        let remaining = self.target_iter.len();
+
+        if remaining > 0 {
+            return Err(ProportionError::RemainingTargetWords { remaining });
+        }
+
+        if self.overage != Rational::zero() {
+            return Err(ProportionError::Overage {
+                overage: self.overage.to_string(),
+            });
+        }
+
+        Ok(())
     }
 
     fn get_next_target_words(&mut self) -> i128 {
+        if let Some(counts) = self.weighted_counts.as_mut() {
+            return counts.pop_front().unwrap_or(0);
+        }
+
         trace!("Getting num target words");
         trace!("Current overage is {:?}", self.overage);
         match self.overage.cmp(&Rational::zero()) {
@@ -103,6 +250,34 @@ impl<'a> ProportionalIter<'a> {
     }
 }
 
+/// The number of target units given to each source word. The share of the target covered
+/// once a word has been given its units is rounded to the nearest unit so that the counts
+/// always add up to the number of target units.
+fn weighted_counts(source: &[&str], target_len: usize, weighting: Weighting) -> VecDeque<i128> {
+    let weights: Vec<i128> = source.iter().map(|w| weighting.weight(w)).collect();
+    let total: i128 = weights.iter().sum();
+    let half = Rational::new(1, 2);
+
+    let mut covered = 0;
+    let mut cumulative_weight = 0;
+
+    weights
+        .iter()
+        .map(|weight| {
+            cumulative_weight += weight;
+
+            let share = Rational::new(target_len as i128 * cumulative_weight, total);
+            let (end, _) = (share + half).mixed_fraction();
+            let count = end - covered;
+
+            trace!("Weight {} of {} covers target up to {}", weight, total, share);
+
+            covered = end;
+            count
+        })
+        .collect()
+}
+
 impl<'a> Iterator for ProportionalIter<'a> {
     type Item = (&'a str, Vec<&'a str>);
 
@@ -115,22 +290,18 @@ impl<'a> Iterator for ProportionalIter<'a> {
                     .take(num_target_words as usize)
                     .collect();
 
-                assert_eq!(target.len() as i128, num_target_words,
-                           "Not enough target words found! {:?}", self);
+                if target.len() as i128 != num_target_words && self.error.is_none() {
+                    trace!("Not enough target words found! {:?}", self);
+                    self.error = Some(ProportionError::MissingTargetWords {
+                        source_word: source.to_owned(),
+                        expected: num_target_words,
+                        found: target.len(),
+                    });
+                }
 
                 Some((source, target))
             }
-            None => {
-                let next_target = self.target_iter.next();
-
-                assert_eq!(next_target, None,
-                           "Source words exhausted yet target words remain {}. {:?}",
-                           next_target.unwrap(), self);
-
-                assert_eq!(self.overage, Rational::zero(),
-                           "Bookkeeping while iterating is broken. {:?}", self);
-                None
-            }
+            None => None,
         }
     }
 }
@@ -320,4 +491,98 @@ mod tests {
         assert_eq!(iter.next(), Some(("ten", vec![])));
         assert_eq!(iter.next(), None);
     }
+
+    // Source words weighted by characters.
+    //
+    // Source weights: an = 2, Verletzungen = 12
+    // Total weight: 14
+    // Target length: 4
+    //
+    // source word   covered share   rounded end   target words
+    // an            4 * 2/14        1             1
+    // Verletzungen  4 * 14/14       4             3
+    #[test]
+    fn weighted_characters_long_compound() {
+        let mut iter = ProportionalIter::from_weighted_units(
+            vec!["an", "Verletzungen"],
+            vec!["of", "injuries", "sustained", "there"],
+            Weighting::Characters,
+        );
+
+        assert_eq!(iter.next(), Some(("an", vec!["of"])));
+        assert_eq!(iter.next(), Some(("Verletzungen", vec!["injuries", "sustained", "there"])));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.finish(), Ok(()));
+    }
+
+    // Source words weighted by syllables.
+    //
+    // Source weights: Sie = 1, starben = 2, an = 1, Verletzungen = 4
+    // Total weight: 8
+    // Target length: 6
+    //
+    // source word   covered share   rounded end   target words
+    // Sie           6 * 1/8         1             1
+    // starben       6 * 3/8         2             1
+    // an            6 * 4/8         3             1
+    // Verletzungen  6 * 8/8         6             3
+    #[test]
+    fn weighted_syllables() {
+        let mut iter = ProportionalIter::from_weighted_units(
+            vec!["Sie", "starben", "an", "Verletzungen"],
+            vec!["They", "died", "of", "their", "many", "injuries"],
+            Weighting::Syllables,
+        );
+
+        assert_eq!(iter.next(), Some(("Sie", vec!["They"])));
+        assert_eq!(iter.next(), Some(("starben", vec!["died"])));
+        assert_eq!(iter.next(), Some(("an", vec!["of"])));
+        assert_eq!(iter.next(), Some(("Verletzungen", vec!["their", "many", "injuries"])));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.finish(), Ok(()));
+    }
+
+    // Fewer target words than source words. The counts still add up to the target length.
+    //
+    // Source weights: Das = 3, ist = 3, Fahrradfahren = 13
+    // Total weight: 19
+    // Target length: 1
+    //
+    // source word    covered share   rounded end   target words
+    // Das            1 * 3/19        0             0
+    // ist            1 * 6/19        0             0
+    // Fahrradfahren  1 * 19/19       1             1
+    #[test]
+    fn weighted_characters_source_longer_than_target() {
+        let mut iter = ProportionalIter::from_weighted_units(
+            vec!["Das", "ist", "Fahrradfahren"],
+            vec!["Cycling"],
+            Weighting::Characters,
+        );
+
+        assert_eq!(iter.next(), Some(("Das", vec![])));
+        assert_eq!(iter.next(), Some(("ist", vec![])));
+        assert_eq!(iter.next(), Some(("Fahrradfahren", vec!["Cycling"])));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.finish(), Ok(()));
+    }
+
+    #[test]
+    fn finish_after_all_words() {
+        let mut iter = ProportionalIter::new("one two", "one' two' three'");
+
+        assert_eq!(iter.by_ref().count(), 2);
+        assert_eq!(iter.finish(), Ok(()));
+    }
+
+    #[test]
+    fn finish_with_target_words_but_no_source_words() {
+        let mut iter = ProportionalIter::new("", "one' two'");
+
+        assert_eq!(iter.next(), None);
+        assert_eq!(
+            iter.finish(),
+            Err(ProportionError::RemainingTargetWords { remaining: 2 })
+        );
+    }
 }
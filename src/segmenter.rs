@@ -0,0 +1,72 @@
+use crate::tokenizer::{is_unspaced_lang, GraphemeTokenizer, Tokenizer, UnicodeTokenizer};
+
+/// Splits target text into the units that are distributed over the source words.
+pub trait Segmenter {
+    fn segment<'a>(&self, text: &'a str) -> Vec<&'a str>;
+
+    /// The text placed between units when they are joined back together.
+    fn separator(&self) -> &str;
+
+    fn join(&self, units: &[&str]) -> String {
+        units.join(self.separator())
+    }
+}
+
+/// Splits on white space. Suitable for languages that separate their words with spaces.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct WhitespaceSegmenter;
+
+impl Segmenter for WhitespaceSegmenter {
+    fn segment<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        UnicodeTokenizer.words(text)
+    }
+
+    fn separator(&self) -> &str {
+        " "
+    }
+}
+
+/// Splits into grapheme clusters leaving out white space. Suitable for languages like
+/// Japanese and Chinese that do not separate their words with spaces.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct GraphemeSegmenter;
+
+impl Segmenter for GraphemeSegmenter {
+    fn segment<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        GraphemeTokenizer.words(text)
+    }
+
+    fn separator(&self) -> &str {
+        ""
+    }
+}
+
+/// Picks the segmenter for a language code such as "ja" or "zh-Hans".
+pub fn segmenter_for_lang(lang: &str) -> Box<dyn Segmenter> {
+    if is_unspaced_lang(lang) {
+        Box::new(GraphemeSegmenter)
+    } else {
+        Box::new(WhitespaceSegmenter)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn segments_graphemes_without_spaces() {
+        assert_eq!(
+            GraphemeSegmenter.segment("日本 語が\u{306F}\u{309A}"),
+            vec!["日", "本", "語", "が", "\u{306F}\u{309A}"]
+        );
+    }
+
+    #[test]
+    fn picks_segmenter_from_language() {
+        assert_eq!(segmenter_for_lang("ja").separator(), "");
+        assert_eq!(segmenter_for_lang("zh-Hans").separator(), "");
+        assert_eq!(segmenter_for_lang("en").separator(), " ");
+        assert_eq!(segmenter_for_lang("und").separator(), " ");
+    }
+}
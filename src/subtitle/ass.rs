@@ -0,0 +1,300 @@
+use std::fmt::Write;
+
+use anyhow::anyhow;
+use serde::{de::Error, Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use srtlib::{Subtitles, Timestamp};
+
+use super::time::to_millis;
+
+/// ASS alignment of text at the bottom center of the video.
+const BOTTOM_CENTER: u8 = 2;
+
+/// ASS alignment of text at the top center of the video.
+const TOP_CENTER: u8 = 8;
+
+/// How the text of one language is shown. Fields that are not set keep the defaults of
+/// the source style.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AssStyle {
+    pub font_name: String,
+
+    /// The font size in pixels of the script resolution.
+    pub font_size: u32,
+
+    /// The text colour as #RRGGBB.
+    pub colour: String,
+
+    /// The outline colour as #RRGGBB.
+    pub outline_colour: String,
+
+    pub bold: bool,
+    pub italic: bool,
+
+    /// The width of the outline in pixels.
+    pub outline: u32,
+
+    /// Where the text is placed as on a numpad. 2 is bottom center and 8 is top center.
+    pub alignment: u8,
+
+    /// The distance in pixels from the top or bottom edge of the video.
+    pub margin_v: u32,
+}
+
+impl AssStyle {
+    /// White text at the bottom.
+    pub fn source() -> Self {
+        AssStyle {
+            font_name: "Arial".to_owned(),
+            font_size: 56,
+            colour: "#FFFFFF".to_owned(),
+            outline_colour: "#000000".to_owned(),
+            bold: false,
+            italic: false,
+            outline: 2,
+            alignment: BOTTOM_CENTER,
+            margin_v: 40,
+        }
+    }
+
+    /// Smaller yellow text at the top.
+    pub fn target() -> Self {
+        AssStyle {
+            font_size: 44,
+            colour: "#FFFF00".to_owned(),
+            alignment: TOP_CENTER,
+            ..AssStyle::source()
+        }
+    }
+}
+
+impl Default for AssStyle {
+    fn default() -> Self {
+        AssStyle::source()
+    }
+}
+
+/// The styles of the source and the target text of a dual language ASS file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AssOptions {
+    /// White text at the bottom unless changed.
+    pub source: AssStyle,
+
+    /// Smaller yellow text at the top unless changed. Fields that are not set keep the
+    /// defaults of the target style.
+    #[serde(deserialize_with = "deserialize_target_style")]
+    pub target: AssStyle,
+}
+
+impl Default for AssOptions {
+    fn default() -> Self {
+        AssOptions {
+            source: AssStyle::source(),
+            target: AssStyle::target(),
+        }
+    }
+}
+
+/// Deserializes the fields that are set over the target style so that the fields that are
+/// not set do not fall back to the source style as the Default of AssStyle does.
+fn deserialize_target_style<'de, D: Deserializer<'de>>(deserializer: D) -> Result<AssStyle, D::Error> {
+    let fields = serde_json::Map::deserialize(deserializer)?;
+
+    let mut style = match serde_json::to_value(AssStyle::target()).map_err(D::Error::custom)? {
+        Value::Object(style) => style,
+        _ => return Err(D::Error::custom("An ASS style must serialize to an object")),
+    };
+
+    style.extend(fields);
+
+    serde_json::from_value(Value::Object(style)).map_err(D::Error::custom)
+}
+
+/// An ASS script with the source subtitles and the target subtitles as two styles. The
+/// cues of each are shown at their own times so they do not need to line up.
+pub fn dual_subtitles(
+    source: &Subtitles,
+    target: &Subtitles,
+    options: &AssOptions,
+) -> Result<String, anyhow::Error> {
+    let mut script = String::new();
+
+    writeln!(script, "[Script Info]")?;
+    writeln!(script, "ScriptType: v4.00+")?;
+    writeln!(script, "PlayResX: 1920")?;
+    writeln!(script, "PlayResY: 1080")?;
+    writeln!(script, "WrapStyle: 0")?;
+    writeln!(script)?;
+
+    writeln!(script, "[V4+ Styles]")?;
+    writeln!(
+        script,
+        "Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, \
+        BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, \
+        BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding"
+    )?;
+    writeln!(script, "{}", style_line("Source", &options.source)?)?;
+    writeln!(script, "{}", style_line("Target", &options.target)?)?;
+    writeln!(script)?;
+
+    writeln!(script, "[Events]")?;
+    writeln!(script, "Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text")?;
+
+    for (style, subtitles) in [("Source", source), ("Target", target)] {
+        for subtitle in subtitles {
+            if subtitle.text.trim().is_empty() {
+                continue;
+            }
+
+            writeln!(
+                script,
+                "Dialogue: 0,{},{},{},,0,0,0,,{}",
+                format_timestamp(&subtitle.start_time),
+                format_timestamp(&subtitle.end_time),
+                style,
+                ass_text(&subtitle.text)
+            )?;
+        }
+    }
+
+    Ok(script)
+}
+
+fn style_line(name: &str, style: &AssStyle) -> Result<String, anyhow::Error> {
+    Ok(format!(
+        "Style: {},{},{},{},&H000000FF,{},&H80000000,{},{},0,0,100,100,0,0,1,{},0,{},20,20,{},1",
+        name,
+        style.font_name,
+        style.font_size,
+        ass_colour(&style.colour)?,
+        ass_colour(&style.outline_colour)?,
+        ass_bool(style.bold),
+        ass_bool(style.italic),
+        style.outline,
+        style.alignment,
+        style.margin_v,
+    ))
+}
+
+/// Converts #RRGGBB to the &HAABBGGRR form of ASS.
+fn ass_colour(colour: &str) -> Result<String, anyhow::Error> {
+    let hex = colour
+        .strip_prefix('#')
+        .filter(|hex| hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+        .ok_or(anyhow!("The colour [{}] must be given as #RRGGBB", colour))?;
+
+    Ok(format!("&H00{}{}{}", &hex[4..6], &hex[2..4], &hex[0..2]).to_uppercase())
+}
+
+fn ass_bool(value: bool) -> i8 {
+    match value {
+        true => -1,
+        false => 0,
+    }
+}
+
+/// Formats the timestamp as h:mm:ss.cc
+fn format_timestamp(timestamp: &Timestamp) -> String {
+    let centis = to_millis(timestamp) / 10;
+
+    format!(
+        "{}:{:02}:{:02}.{:02}",
+        centis / 360_000,
+        centis / 6000 % 60,
+        centis / 100 % 60,
+        centis % 100
+    )
+}
+
+/// Converts the text of an SRT cue to ASS. Line breaks become \N, the formatting tags
+/// become override tags and any other tag is dropped.
+fn ass_text(text: &str) -> String {
+    let mut ass = String::new();
+    let mut rest = text.trim();
+
+    while let Some(open) = rest.find('<') {
+        ass.push_str(&plain_text(&rest[..open]));
+        rest = &rest[open..];
+
+        let close = match rest.find('>') {
+            Some(close) => close,
+            None => break,
+        };
+
+        let tag = rest[1..close].to_lowercase();
+
+        match tag.as_str() {
+            "b" | "i" | "u" => ass.push_str(&format!("{{\\{}1}}", tag)),
+            "/b" | "/i" | "/u" => ass.push_str(&format!("{{\\{}0}}", &tag[1..])),
+            _ => {}
+        }
+
+        rest = &rest[close + 1..];
+    }
+
+    ass.push_str(&plain_text(rest));
+
+    ass.replace("\r\n", "\n").replace('\n', "\\N")
+}
+
+/// Replaces the braces of text with full width braces, as renderers take a brace for the
+/// start of an override block and drop what follows. Not every renderer unescapes \{.
+fn plain_text(text: &str) -> String {
+    text.replace('{', "｛").replace('}', "｝")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::subtitle::util::test_util::timed_subtitles;
+
+    #[test]
+    fn writes_both_languages_as_styles() {
+        let source = timed_subtitles(vec![("00:00:01,000", "00:00:03,500", "Es geht um\n<i>Mountainbiker</i>.")]);
+        let target = timed_subtitles(vec![("00:00:01,000", "00:00:03,500", "It's about mountain bikers.")]);
+
+        let script = dual_subtitles(&source, &target, &AssOptions::default()).unwrap();
+
+        assert!(script.contains("Style: Source,Arial,56,&H00FFFFFF,&H000000FF,&H00000000,"));
+        assert!(script.contains("Style: Target,Arial,44,&H0000FFFF,&H000000FF,&H00000000,"));
+        assert!(script.contains(
+            "Dialogue: 0,0:00:01.00,0:00:03.50,Source,,0,0,0,,Es geht um\\N{\\i1}Mountainbiker{\\i0}.\n"
+        ));
+        assert!(script.contains("Dialogue: 0,0:00:01.00,0:00:03.50,Target,,0,0,0,,It's about mountain bikers.\n"));
+    }
+
+    #[test]
+    fn replaces_braces_of_the_text() {
+        assert_eq!(ass_text("<i>{Musik}</i> und }{"), "{\\i1}｛Musik｝{\\i0} und ｝｛");
+    }
+
+    #[test]
+    fn places_styles_by_alignment() {
+        let options = AssOptions::default();
+
+        assert!(style_line("Source", &options.source).unwrap().ends_with(",2,20,20,40,1"));
+        assert!(style_line("Target", &options.target).unwrap().ends_with(",8,20,20,40,1"));
+    }
+
+    #[test]
+    fn keeps_defaults_of_each_side_for_fields_not_set() {
+        let options: AssOptions = serde_yaml::from_str("source:\n  bold: true\ntarget:\n  font_size: 40\n").unwrap();
+
+        assert_eq!(options.source, AssStyle { bold: true, ..AssStyle::source() });
+        assert_eq!(options.target, AssStyle { font_size: 40, ..AssStyle::target() });
+        assert_eq!(serde_yaml::from_str::<AssOptions>("{}").unwrap(), AssOptions::default());
+    }
+
+    #[test]
+    fn rejects_malformed_colours() {
+        assert_eq!(ass_colour("#12A4f6").unwrap(), "&H00F6A412");
+        assert!(ass_colour("yellow").is_err());
+    }
+
+    #[test]
+    fn formats_hours_without_padding() {
+        assert_eq!(format_timestamp(&Timestamp::new(1, 2, 3, 456)), "1:02:03.45");
+    }
+}
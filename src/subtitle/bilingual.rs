@@ -0,0 +1,129 @@
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use srtlib::{Subtitle, Subtitles};
+
+/// How the translated text is marked in a bilingual SRT file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BilingualOptions {
+    /// Wraps the translated text in <font color="..."> when set. Players take a name such
+    /// as "yellow" or a colour given as #RRGGBB.
+    pub colour: Option<String>,
+
+    /// Wraps the translated text in <i>.
+    pub italic: bool,
+}
+
+/// Subtitles with the timing of the source cues that show the translated text of each cue
+/// below its source text. The target must hold a cue for every source cue, as the aligned
+/// translation does.
+pub fn bilingual_cues(
+    source: &Subtitles,
+    target: &Subtitles,
+    options: &BilingualOptions,
+) -> Result<Subtitles, anyhow::Error> {
+    let source: Vec<&Subtitle> = source.into_iter().collect();
+    let target: Vec<&Subtitle> = target.into_iter().collect();
+
+    if source.len() != target.len() {
+        return Err(anyhow!(
+            "The translation has {} cues but the source has {}. Bilingual subtitles need a \
+            translated cue for every source cue",
+            target.len(),
+            source.len()
+        ));
+    }
+
+    let cues = source
+        .into_iter()
+        .zip(target)
+        .map(|(s, t)| {
+            let text = match t.text.trim() {
+                "" => s.text.trim().to_owned(),
+                target_text => format!("{}\n{}", s.text.trim(), mark_target(target_text, options)),
+            };
+
+            Subtitle::new(s.num, s.start_time, s.end_time, text)
+        })
+        .collect();
+
+    Ok(Subtitles::new_from_vec(cues))
+}
+
+fn mark_target(text: &str, options: &BilingualOptions) -> String {
+    let mut text = text.to_owned();
+
+    if options.italic {
+        text = format!("<i>{}</i>", text);
+    }
+
+    if let Some(colour) = &options.colour {
+        text = format!("<font color=\"{}\">{}</font>", colour, text);
+    }
+
+    text
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::subtitle::util::test_util::{subtitles, timed_subtitles};
+
+    #[test]
+    fn shows_target_below_source_with_source_timing() {
+        let source = timed_subtitles(vec![
+            ("00:00:01,000", "00:00:02,000", "Hallo."),
+            ("00:00:03,000", "00:00:04,500", "Wie geht's?"),
+        ]);
+        let target = subtitles(vec!["Hello.", "How are you?"]);
+
+        let bilingual = bilingual_cues(&source, &target, &BilingualOptions::default()).unwrap();
+
+        assert_eq!(
+            bilingual.to_string(),
+            timed_subtitles(vec![
+                ("00:00:01,000", "00:00:02,000", "Hallo.\nHello."),
+                ("00:00:03,000", "00:00:04,500", "Wie geht's?\nHow are you?"),
+            ])
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn marks_target_with_colour_and_italics() {
+        let options = BilingualOptions {
+            colour: Some("#FFFF00".to_owned()),
+            italic: true,
+        };
+
+        let bilingual = bilingual_cues(&subtitles(vec!["Hallo."]), &subtitles(vec!["Hello."]), &options).unwrap();
+
+        assert_eq!(
+            bilingual.to_vec()[0].text,
+            "Hallo.\n<font color=\"#FFFF00\"><i>Hello.</i></font>"
+        );
+    }
+
+    #[test]
+    fn keeps_source_alone_when_cue_has_no_translation() {
+        let bilingual = bilingual_cues(
+            &subtitles(vec!["Hallo.", "Tschüss."]),
+            &subtitles(vec!["Hello. Bye.", ""]),
+            &BilingualOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(bilingual.to_vec()[1].text, "Tschüss.");
+    }
+
+    #[test]
+    fn rejects_target_with_other_cue_count() {
+        let result = bilingual_cues(
+            &subtitles(vec!["Hallo.", "Tschüss."]),
+            &subtitles(vec!["Hello. Bye."]),
+            &BilingualOptions::default(),
+        );
+
+        assert!(result.is_err());
+    }
+}
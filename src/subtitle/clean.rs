@@ -4,6 +4,8 @@ use srtlib::{Subtitles, Subtitle};
 use fancy_regex::Regex;
 use lazy_static::lazy_static;
 
+use crate::tokenizer::Tokenizer;
+
 lazy_static! {
     // Regex that matches an HTML tag.
     static ref HTML_TAG_RE: Regex = Regex::new("<.*?>").unwrap();
@@ -22,27 +24,31 @@ lazy_static! {
     // 581
     // 00:16:43,770 --> 00:16:45,360
     // Und die haben vielleicht mal für Y-
-    static ref END_SUBTITLE_TEXT_WITH_HYPHEN: Regex = Regex::new(r"([A-Za-z])-$").unwrap();
+    static ref END_SUBTITLE_TEXT_WITH_HYPHEN: Regex = Regex::new(r"(\p{L})-$").unwrap();
 
     // A subtitle line where a line ends with a hyphenated word. 
     // 44
     // 00:02:19,320 --> 00:02:23,440
     // dass 70% der Insel und des um-
     // liegenden Archipels zerstört wurden.
-    static ref MID_SUBTITLE_HYPHENATED_WORD: Regex = Regex::new(r"([A-Za-z])-\n(\w*)\s").unwrap();
+    static ref MID_SUBTITLE_HYPHENATED_WORD: Regex = Regex::new(r"(\p{L})-\n(\w*)\s").unwrap();
 }
 
-pub fn clean_subtitles(subtitles: &mut Subtitles) {
+/// Cleans the text of the subtitles. The tokenizer of the language of the subtitles decides
+/// what is space and where the words are, the same as when the text is extracted and
+/// aligned.
+pub fn clean_subtitles(subtitles: &mut Subtitles, tokenizer: &dyn Tokenizer) {
     for subtitle in subtitles.into_iter() {
-        subtitle.text = clean(&subtitle.text);
+        subtitle.text = clean(&subtitle.text, tokenizer);
     }
 
     for (subtitle, subtitle_next) in subtitles.into_iter().tuples() {
-        adjust_hyphen_ending_subtitle(subtitle, subtitle_next)
+        adjust_hyphen_ending_subtitle(subtitle, subtitle_next, tokenizer)
     }
 }
 
-fn clean(text: &str) -> String {
+fn clean(text: &str, tokenizer: &dyn Tokenizer) -> String {
+    let text = normalize_spaces(text, tokenizer);
     let text = HTML_TAG_RE.replace_all(&text, "");
     let text = MULTI_SPACE_RE.replace_all(&text, " ");
     let text = CARRIAGE_RETURN_RE.replace_all(&text, " ");
@@ -51,6 +57,25 @@ fn clean(text: &str) -> String {
     return text.trim().to_owned();
 }
 
+/// Replaces every kind of space the tokenizer splits words on, such as no-break and zero
+/// width spaces, with a plain space so that the later steps only have to handle those.
+fn normalize_spaces(text: &str, tokenizer: &dyn Tokenizer) -> String {
+    tokenizer
+        .tokens(text)
+        .into_iter()
+        .map(|token| match tokenizer.is_space(token) {
+            true => token
+                .chars()
+                .map(|c| match c {
+                    '\n' | '\r' => c,
+                    _ => ' ',
+                })
+                .collect(),
+            false => token.to_owned(),
+        })
+        .collect()
+}
+
 /// Consider the subtitles
 ///
 /// 581
@@ -64,18 +89,23 @@ fn clean(text: &str) -> String {
 /// The word at the end of subtitle 581 is Y-Kollektiv but unfortunately
 /// Kollektiv has been moved to the next subtitle. This can cause issues later
 /// in the process when trying to align subtitles with translations. If we see
-/// this pattern of a subtitle text ending with r"([A-Za-z])-\n" then lets pull
+/// this pattern of a subtitle text ending with r"(\p{L})-$" then lets pull
 /// the first word of the next line up.
-fn adjust_hyphen_ending_subtitle(subtitle: &mut Subtitle, subtitle_next: &mut Subtitle) {
+fn adjust_hyphen_ending_subtitle(subtitle: &mut Subtitle, subtitle_next: &mut Subtitle, tokenizer: &dyn Tokenizer) {
     if END_SUBTITLE_TEXT_WITH_HYPHEN
         .is_match(&subtitle.text)
         .unwrap()
     {
-        if let Some(idx) = subtitle_next.text.find(" ") {
-            let word = subtitle_next.text[0..idx].to_owned();
+        let tokens = tokenizer.tokens(&subtitle_next.text);
+
+        if let [word, space, ..] = tokens.as_slice() {
+            if !tokenizer.is_space(word) && tokenizer.is_space(space) {
+                let word = word.to_string();
+                let len = word.len() + space.len();
 
-            subtitle_next.text.replace_range(0..idx + 1, "");
-            subtitle.text.push_str(word.trim());
+                subtitle_next.text.replace_range(0..len, "");
+                subtitle.text.push_str(&word);
+            }
         }
     }
 }
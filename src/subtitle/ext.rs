@@ -1,23 +1,17 @@
-use fancy_regex::Regex;
-use lazy_static::lazy_static;
 use srtlib::Subtitle;
 
-lazy_static! {
-    static ref SPACES_RE: Regex = Regex::new(r"(\S+|\s+)").expect("Invalid regex");
-}
+use crate::tokenizer::Tokenizer;
 
 pub trait SubtitleExt<'a> {
-    /// Returns an iterator over all of the words and spaces/newlines of the
-    /// subtitle text.
-    /// For example, "  This is  some text. " would return an iterator with the items
-    /// "  ", "This", " ", "is", "  ", "some", " ", "text."
-    fn words_and_spaces(&'a self) -> Box<dyn Iterator<Item=&'a str> + 'a>;
+    /// Returns all of the words and spaces/newlines of the subtitle text as split by the
+    /// tokenizer.
+    /// For example, "  This is  some text. " would return the items
+    /// "  ", "This", " ", "is", "  ", "some", " ", "text.", " "
+    fn words_and_spaces(&'a self, tokenizer: &dyn Tokenizer) -> Vec<&'a str>;
 }
 
-// TODO fix unwrap
 impl<'a> SubtitleExt<'a> for Subtitle {
-    fn words_and_spaces(&'a self) -> Box<dyn Iterator<Item=&'a str> + 'a> {
-        Box::new(SPACES_RE.captures_iter(&self.text)
-            .map(|c| c.unwrap().get(1).map_or("", |m| m.as_str())))
+    fn words_and_spaces(&'a self, tokenizer: &dyn Tokenizer) -> Vec<&'a str> {
+        tokenizer.tokens(&self.text)
     }
-}
\ No newline at end of file
+}
@@ -3,43 +3,60 @@ use itertools::Itertools;
 use lazy_static::lazy_static;
 use srtlib::Subtitles;
 
+use crate::tokenizer::Tokenizer;
+
 lazy_static! {
-    // Regex that matches a line that ends with an alphanumeric, comma, collon, or semi-colon.
-    static ref JOIN_RE: Regex = Regex::new(r"([A-Za-z0-9,;:])\n").unwrap();
+    // Regex that matches a line that ends with a letter, number, comma, collon, or semi-colon.
+    static ref JOIN_RE: Regex = Regex::new(r"([\p{L}\p{N},;:])\n").unwrap();
 
     // Regex that matches an initial.
-    static ref INITIAL_RE: Regex = Regex::new(r"(\s[A-Z0-9][.])\n").unwrap();
+    static ref INITIAL_RE: Regex = Regex::new(r"(\s[\p{Lu}\p{N}][.])\n").unwrap();
 
     // A line that ends with a hyphen but has a space before it.
     static ref END_HYPHEN: Regex = Regex::new(r"\s-\n").unwrap();
 }
 
-pub fn extract_text(subtitles: &Subtitles) -> String {
+/// The text of the subtitles with the lines of a sentence joined. The tokenizer of the
+/// language of the subtitles decides what space is trimmed and what joins the lines.
+pub fn extract_text(subtitles: &Subtitles, tokenizer: &dyn Tokenizer) -> String {
     let x = subtitles
         .into_iter()
-        .map(|s| s.text.lines().map(|l| l.trim()).join("\n"))
+        .map(|s| s.text.lines().map(|l| tokenizer.trim(l)).join("\n"))
         .join("\n");
 
-    return join_sentences(x);
+    return join_sentences(x, tokenizer);
 }
 
-fn join_sentences(text: String) -> String {
-    let text = JOIN_RE.replace_all(&text, "$1 ");
+fn join_sentences(text: String, tokenizer: &dyn Tokenizer) -> String {
+    let text = JOIN_RE.replace_all(&text, format!("$1{}", tokenizer.separator()));
     let text = INITIAL_RE.replace_all(&text, "$1 ");
     let text = END_HYPHEN.replace_all(&text, " - ");
     return text.to_string();
 }
 
+/// Whether the text ends with sentence final punctuation, ignoring closing quotes and
+/// brackets.
+pub fn ends_sentence(text: &str) -> bool {
+    text.trim_end()
+        .trim_end_matches(['"', '\'', ')', '»', '“', '”', '」'])
+        .chars()
+        .last()
+        .is_some_and(|c| ".?!…。？！".contains(c))
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::subtitle::util::test_util::subtitles;
+    use crate::{
+        subtitle::util::test_util::subtitles,
+        tokenizer::{GraphemeTokenizer, UnicodeTokenizer},
+    };
 
     #[test]
     fn treat_single_uppercase_letter_followed_by_period_as_part_of_sentence() {
         let subtitles = subtitles(vec!["7 h später stirbt Susanne F.\nan ihren Verletzungen."]);
 
-        let text = extract_text(&subtitles);
+        let text = extract_text(&subtitles, &UnicodeTokenizer);
 
         assert_eq!(text, "7 h später stirbt Susanne F. an ihren Verletzungen.")
     }
@@ -48,7 +65,7 @@ mod test {
     fn treat_line_ending_with_number_followed_by_period_as_part_of_sentence() {
         let subtitles = subtitles(vec!["Heute sieht sie diese Aufnahmen zum 1.\nMal."]);
 
-        let text = extract_text(&subtitles);
+        let text = extract_text(&subtitles, &UnicodeTokenizer);
 
         assert_eq!(text, "Heute sieht sie diese Aufnahmen zum 1. Mal.")
     }
@@ -59,7 +76,7 @@ mod test {
             "Die Folgen - damals wie heute -\nein Problem für die jetzt 33-Jährige.",
         ]);
 
-        let text = extract_text(&subtitles);
+        let text = extract_text(&subtitles, &UnicodeTokenizer);
 
         assert_eq!(
             text,
@@ -75,8 +92,26 @@ mod test {
             "Dann habe ich\neinen Freund angerufen, der bei der Polizei arbeitet.",
         ]);
 
-        let text = extract_text(&subtitles);
+        let text = extract_text(&subtitles, &UnicodeTokenizer);
 
         assert_eq!(text, "Davon jedenfalls geht Sigrid Kamisch aus.\n* Musik *\nDann habe ich einen Freund angerufen, der bei der Polizei arbeitet.")
     }
+
+    #[test]
+    fn join_line_ending_with_non_ascii_letter() {
+        let subtitles = subtitles(vec!["Das ist schön\nund Élise ist froh."]);
+
+        let text = extract_text(&subtitles, &UnicodeTokenizer);
+
+        assert_eq!(text, "Das ist schön und Élise ist froh.")
+    }
+
+    #[test]
+    fn join_lines_without_space_for_unspaced_languages() {
+        let subtitles = subtitles(vec!["\u{3000}今日は天気が\nいいですね。\u{3000}", "明日も\nそうかな"]);
+
+        let text = extract_text(&subtitles, &GraphemeTokenizer);
+
+        assert_eq!(text, "今日は天気がいいですね。\n明日もそうかな")
+    }
 }
@@ -0,0 +1,112 @@
+use std::path::Path;
+
+use anyhow::{anyhow, Context};
+use srtlib::Subtitles;
+
+use super::{timedtext::TimedText, ttml::parse_ttml, vtt::WebVtt};
+
+/// The subtitle file formats that can be read and written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubtitleFormat {
+    Srt,
+    Vtt,
+
+    /// YouTube timed text as JSON. Can only be read.
+    Json3,
+
+    /// YouTube timed text as XML. Can only be read.
+    Srv3,
+
+    /// TTML and its older name DFXP. Can only be read.
+    Ttml,
+}
+
+impl SubtitleFormat {
+    /// The format for a name such as "srt" or "vtt" as used for file extensions.
+    pub fn from_name(name: &str) -> Option<SubtitleFormat> {
+        match name.to_lowercase().as_str() {
+            "srt" => Some(SubtitleFormat::Srt),
+            "vtt" => Some(SubtitleFormat::Vtt),
+            "json3" => Some(SubtitleFormat::Json3),
+            "srv3" => Some(SubtitleFormat::Srv3),
+            "ttml" | "dfxp" | "xml" => Some(SubtitleFormat::Ttml),
+            _ => None,
+        }
+    }
+
+    /// The format of the file at path by its extension.
+    pub fn from_path(path: &Path) -> Option<SubtitleFormat> {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(SubtitleFormat::from_name)
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            SubtitleFormat::Srt => "srt",
+            SubtitleFormat::Vtt => "vtt",
+            SubtitleFormat::Json3 => "json3",
+            SubtitleFormat::Srv3 => "srv3",
+            SubtitleFormat::Ttml => "ttml",
+        }
+    }
+
+    pub fn is_writable(&self) -> bool {
+        matches!(self, SubtitleFormat::Srt | SubtitleFormat::Vtt)
+    }
+
+    pub fn parse(&self, content: &str) -> Result<Subtitles, anyhow::Error> {
+        match self {
+            SubtitleFormat::Srt => {
+                Subtitles::parse_from_str(content.to_owned()).context("Failed to parse SRT subtitles")
+            }
+            SubtitleFormat::Vtt => Ok(WebVtt::parse(content)
+                .context("Failed to parse WebVTT subtitles")?
+                .to_subtitles()),
+            SubtitleFormat::Json3 | SubtitleFormat::Srv3 => Ok(self.parse_timed_text(content)?.to_subtitles()),
+            SubtitleFormat::Ttml => parse_ttml(content).context("Failed to parse TTML subtitles"),
+        }
+    }
+
+    /// Reads YouTube timed text keeping the time of every word.
+    pub fn parse_timed_text(&self, content: &str) -> Result<TimedText, anyhow::Error> {
+        match self {
+            SubtitleFormat::Json3 => TimedText::parse_json3(content),
+            SubtitleFormat::Srv3 => TimedText::parse_srv3(content),
+            _ => Err(anyhow!("{} subtitles do not carry word timings", self.extension())),
+        }
+    }
+
+    pub fn format(&self, subtitles: &Subtitles) -> Result<String, anyhow::Error> {
+        match self {
+            SubtitleFormat::Srt => Ok(subtitles.to_string()),
+            SubtitleFormat::Vtt => Ok(WebVtt::from_subtitles(subtitles).to_string()),
+            SubtitleFormat::Json3 | SubtitleFormat::Srv3 | SubtitleFormat::Ttml => {
+                Err(anyhow!("Subtitles can not be written as {}", self.extension()))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::subtitle::util::test_util::subtitles;
+
+    #[test]
+    fn picks_format_from_extension() {
+        assert_eq!(SubtitleFormat::from_path(Path::new("a/b.de.srt")), Some(SubtitleFormat::Srt));
+        assert_eq!(SubtitleFormat::from_path(Path::new("a/b.en.VTT")), Some(SubtitleFormat::Vtt));
+        assert_eq!(SubtitleFormat::from_path(Path::new("a/b.dfxp")), Some(SubtitleFormat::Ttml));
+        assert_eq!(SubtitleFormat::from_path(Path::new("a/b.txt")), None);
+    }
+
+    #[test]
+    fn converts_between_formats() {
+        let source = subtitles(vec!["Hallo & willkommen"]);
+        let vtt = SubtitleFormat::Vtt.format(&source).unwrap();
+
+        assert_eq!(vtt, "WEBVTT\n\n00:00:00.000 --> 00:00:01.000\nHallo &amp; willkommen\n");
+        assert_eq!(SubtitleFormat::Vtt.parse(&vtt).unwrap(), source);
+    }
+}
@@ -0,0 +1,182 @@
+use serde::{Deserialize, Serialize};
+use srtlib::Subtitles;
+
+use crate::segmenter::Segmenter;
+
+/// The cost of a line break that does not follow punctuation, in squared characters of
+/// imbalance between the lines.
+const BREAK_PENALTY: usize = 25;
+
+/// The cost of every character a line is longer than allowed.
+const OVERFLOW_PENALTY: usize = 1000;
+
+/// Limits for the lines of a subtitle.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LayoutOptions {
+    pub max_chars_per_line: usize,
+    pub max_lines: usize,
+}
+
+impl Default for LayoutOptions {
+    fn default() -> Self {
+        LayoutOptions {
+            max_chars_per_line: 42,
+            max_lines: 2,
+        }
+    }
+}
+
+/// Rewraps the text of every subtitle to the limits of the layout.
+pub fn rewrap_subtitles(subtitles: &mut Subtitles, options: &LayoutOptions, segmenter: &dyn Segmenter) {
+    for subtitle in subtitles.into_iter() {
+        subtitle.text = rewrap(&subtitle.text, options, segmenter);
+    }
+}
+
+/// Breaks the text into as few lines as the maximum line length allows but no more than
+/// the maximum number of lines. The lines are balanced in length and breaks after
+/// punctuation are preferred. Text that does not fit into the maximum number of lines
+/// overflows the maximum line length.
+pub fn rewrap(text: &str, options: &LayoutOptions, segmenter: &dyn Segmenter) -> String {
+    let units = segmenter.segment(text);
+    let separator = segmenter.separator();
+
+    if units.is_empty() {
+        return String::new();
+    }
+
+    let width = |units: &[&str]| -> usize {
+        units.iter().map(|u| u.chars().count()).sum::<usize>()
+            + separator.chars().count() * (units.len() - 1)
+    };
+
+    let total = width(&units);
+    let max_chars = options.max_chars_per_line.max(1);
+    let lines = total.div_ceil(max_chars).clamp(1, options.max_lines.max(1)).min(units.len());
+
+    if lines == 1 {
+        return segmenter.join(&units);
+    }
+
+    let average = total / lines;
+
+    let line_cost = |start: usize, end: usize| -> usize {
+        let line = width(&units[start..end]);
+        let imbalance = line.abs_diff(average);
+        imbalance * imbalance + line.saturating_sub(max_chars) * OVERFLOW_PENALTY
+    };
+
+    let break_cost = |end: usize| -> usize {
+        match ends_with_punctuation(units[end - 1]) {
+            true => 0,
+            false => BREAK_PENALTY,
+        }
+    };
+
+    // cost[l][i] is the cost of putting the first i units on l lines.
+    let n = units.len();
+    let mut cost = vec![vec![usize::MAX; n + 1]; lines + 1];
+    let mut previous = vec![vec![0; n + 1]; lines + 1];
+    cost[0][0] = 0;
+
+    for line in 1..=lines {
+        for end in line..=n {
+            for start in (line - 1)..end {
+                if cost[line - 1][start] == usize::MAX {
+                    continue;
+                }
+
+                let mut candidate = cost[line - 1][start] + line_cost(start, end);
+
+                if end < n {
+                    candidate += break_cost(end);
+                }
+
+                if candidate < cost[line][end] {
+                    cost[line][end] = candidate;
+                    previous[line][end] = start;
+                }
+            }
+        }
+    }
+
+    let mut breaks = Vec::with_capacity(lines);
+    let mut end = n;
+
+    for line in (1..=lines).rev() {
+        let start = previous[line][end];
+        breaks.push(segmenter.join(&units[start..end]));
+        end = start;
+    }
+
+    breaks.reverse();
+    breaks.join("\n")
+}
+
+fn ends_with_punctuation(unit: &str) -> bool {
+    unit.chars()
+        .last()
+        .is_some_and(|c| ",.;:?!，。、；：？！".contains(c))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::segmenter::{GraphemeSegmenter, WhitespaceSegmenter};
+
+    fn options(max_chars_per_line: usize, max_lines: usize) -> LayoutOptions {
+        LayoutOptions {
+            max_chars_per_line,
+            max_lines,
+        }
+    }
+
+    #[test]
+    fn keeps_short_text_on_one_line() {
+        assert_eq!(
+            rewrap("It's about\nmountain bikers", &options(42, 2), &WhitespaceSegmenter),
+            "It's about mountain bikers"
+        );
+    }
+
+    #[test]
+    fn balances_two_lines() {
+        assert_eq!(
+            rewrap(
+                "It's\nabout mountain bikers and the question who may use the forest",
+                &options(42, 2),
+                &WhitespaceSegmenter
+            ),
+            "It's about mountain bikers and the\nquestion who may use the forest"
+        );
+    }
+
+    #[test]
+    fn prefers_breaks_after_punctuation() {
+        assert_eq!(
+            rewrap(
+                "It's about mountain bikers, and who may use the forest here",
+                &options(42, 2),
+                &WhitespaceSegmenter
+            ),
+            "It's about mountain bikers,\nand who may use the forest here"
+        );
+    }
+
+    #[test]
+    fn overflows_when_lines_are_used_up() {
+        assert_eq!(
+            rewrap("one two three four five six", &options(5, 2), &WhitespaceSegmenter),
+            "one two three\nfour five six"
+        );
+    }
+
+    #[test]
+    fn wraps_unspaced_text() {
+        assert_eq!(
+            rewrap("私たちは今日本語を勉強しています。", &options(10, 2), &GraphemeSegmenter),
+            "私たちは今日本語\nを勉強しています。"
+        );
+    }
+}
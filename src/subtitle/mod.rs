@@ -1,7 +1,18 @@
+pub mod ass;
+pub mod bilingual;
 pub mod clean;
 pub mod ext;
 pub mod extract;
+pub mod format;
+pub mod layout;
+pub mod pair;
 pub mod path;
+pub mod report;
+pub mod retime;
+pub mod time;
+pub mod timedtext;
 pub mod translation;
+pub mod ttml;
 pub mod util;
+pub mod vtt;
 pub mod write;
\ No newline at end of file
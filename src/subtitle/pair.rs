@@ -0,0 +1,267 @@
+use itertools::Itertools;
+use srtlib::{Subtitle, Subtitles, Timestamp};
+
+use crate::{tokenizer::Tokenizer, translation::Translation};
+
+use super::{
+    extract::ends_sentence,
+    time::{from_millis, to_millis},
+};
+
+/// A source and a target sentence are paired when they overlap for at least this share of
+/// the shorter of the two.
+const MIN_OVERLAP_SHARE: f64 = 0.5;
+
+/// Source sentences and the target sentences shown at the same time.
+#[derive(Debug, Clone)]
+pub struct SentencePair {
+    pub start_time: Timestamp,
+    pub end_time: Timestamp,
+    pub translation: Translation,
+}
+
+/// The sentences of consecutive subtitles and when they are shown.
+#[derive(Debug)]
+struct Sentence {
+    start: u64,
+    end: u64,
+    text: String,
+}
+
+impl Sentence {
+    fn duration(&self) -> u64 {
+        self.end.saturating_sub(self.start)
+    }
+
+    /// Whether the two sentences are shown at the same time for long enough to be
+    /// translations of each other.
+    fn overlaps(&self, other: &Sentence) -> bool {
+        let overlap = self.end.min(other.end).saturating_sub(self.start.max(other.start));
+        let shorter = self.duration().min(other.duration()).max(1);
+
+        overlap as f64 >= MIN_OVERLAP_SHARE * shorter as f64
+    }
+}
+
+/// Pairs the sentences of subtitles in one language with the sentences of subtitles of the
+/// same video in another language by the time they are shown. Where the sentences of one
+/// side overlap with more than one sentence of the other they are paired as a group.
+/// Sentences that overlap with nothing on the other side are left out. Each side is split
+/// into words by the tokenizer of its language.
+pub fn pair_subtitles(
+    source: &Subtitles,
+    target: &Subtitles,
+    source_tokenizer: &dyn Tokenizer,
+    target_tokenizer: &dyn Tokenizer,
+) -> Vec<SentencePair> {
+    let source = sentences(source, source_tokenizer);
+    let target = sentences(target, target_tokenizer);
+
+    let mut pairs = Vec::new();
+    let (mut i, mut j) = (0, 0);
+
+    while i < source.len() && j < target.len() {
+        if !source[i].overlaps(&target[j]) {
+            if source[i].start <= target[j].start {
+                i += 1;
+            } else {
+                j += 1;
+            }
+            continue;
+        }
+
+        let (mut source_end, mut target_end) = (i + 1, j + 1);
+
+        loop {
+            if source_end < source.len() && source[source_end].overlaps(&target[target_end - 1]) {
+                source_end += 1;
+            } else if target_end < target.len() && target[target_end].overlaps(&source[source_end - 1]) {
+                target_end += 1;
+            } else {
+                break;
+            }
+        }
+
+        pairs.push(pair(
+            &source[i..source_end],
+            &target[j..target_end],
+            source_tokenizer.separator(),
+            target_tokenizer.separator(),
+        ));
+
+        i = source_end;
+        j = target_end;
+    }
+
+    pairs
+}
+
+/// The source and the target sentences of the pairs as subtitles with a cue for every
+/// pair, shown while the pair is.
+pub fn pair_cues(pairs: &[SentencePair]) -> (Subtitles, Subtitles) {
+    let cues = |text: fn(&Translation) -> &str| {
+        let cues = pairs
+            .iter()
+            .enumerate()
+            .map(|(index, p)| Subtitle::new(index + 1, p.start_time, p.end_time, text(&p.translation).to_owned()))
+            .collect();
+
+        Subtitles::new_from_vec(cues)
+    };
+
+    (cues(|t| &t.source_text), cues(|t| &t.target_text))
+}
+
+fn pair(source: &[Sentence], target: &[Sentence], source_separator: &str, target_separator: &str) -> SentencePair {
+    let start = source.iter().chain(target).map(|s| s.start).min().unwrap_or(0);
+    let end = source.iter().chain(target).map(|s| s.end).max().unwrap_or(0);
+
+    SentencePair {
+        start_time: from_millis(start),
+        end_time: from_millis(end),
+        translation: Translation::new(
+            &source.iter().map(|s| s.text.as_str()).join(source_separator),
+            &target.iter().map(|s| s.text.as_str()).join(target_separator),
+        ),
+    }
+}
+
+/// Groups consecutive subtitles into sentences. A sentence ends with the subtitle that
+/// ends in sentence final punctuation.
+fn sentences(subtitles: &Subtitles, tokenizer: &dyn Tokenizer) -> Vec<Sentence> {
+    let mut sentences = Vec::new();
+    let mut current: Option<Sentence> = None;
+
+    for subtitle in subtitles {
+        let text = tokenizer.words(&subtitle.text).join(tokenizer.separator());
+
+        if text.is_empty() {
+            continue;
+        }
+
+        let sentence = current.get_or_insert(Sentence {
+            start: to_millis(&subtitle.start_time),
+            end: 0,
+            text: String::new(),
+        });
+
+        if !sentence.text.is_empty() {
+            sentence.text.push_str(tokenizer.separator());
+        }
+
+        sentence.text.push_str(&text);
+        sentence.end = to_millis(&subtitle.end_time);
+
+        if ends_sentence(&text) {
+            sentences.extend(current.take());
+        }
+    }
+
+    sentences.extend(current);
+    sentences
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        subtitle::util::test_util::timed_subtitles,
+        tokenizer::{GraphemeTokenizer, UnicodeTokenizer},
+    };
+
+    fn translations(pairs: &[SentencePair]) -> Vec<(&str, &str)> {
+        pairs
+            .iter()
+            .map(|p| (p.translation.source_text.as_str(), p.translation.target_text.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn pairs_sentences_shown_at_the_same_time() {
+        let source = timed_subtitles(vec![
+            ("00:00:01,000", "00:00:03,000", "Es geht um\nMountainbiker."),
+            ("00:00:03,500", "00:00:05,000", "Wer darf hier"),
+            ("00:00:05,000", "00:00:07,000", "den Wald nutzen?"),
+        ]);
+
+        let target = timed_subtitles(vec![
+            ("00:00:01,100", "00:00:03,100", "It's about mountain bikers."),
+            ("00:00:03,600", "00:00:07,000", "Who may use the forest here?"),
+        ]);
+
+        let pairs = pair_subtitles(&source, &target, &UnicodeTokenizer, &UnicodeTokenizer);
+
+        assert_eq!(
+            translations(&pairs),
+            vec![
+                ("Es geht um Mountainbiker.", "It's about mountain bikers."),
+                ("Wer darf hier den Wald nutzen?", "Who may use the forest here?"),
+            ]
+        );
+
+        assert_eq!(pairs[1].start_time.to_string(), "00:00:03,500");
+        assert_eq!(pairs[1].end_time.to_string(), "00:00:07,000");
+    }
+
+    #[test]
+    fn groups_sentences_split_differently() {
+        let source = timed_subtitles(vec![
+            ("00:00:00,000", "00:00:02,000", "Ja."),
+            ("00:00:02,000", "00:00:04,000", "Das ist gut."),
+            ("00:00:05,000", "00:00:07,000", "Danke."),
+        ]);
+
+        let target = timed_subtitles(vec![
+            ("00:00:00,000", "00:00:04,000", "Yes, that is good."),
+            ("00:00:05,000", "00:00:07,000", "Thanks."),
+        ]);
+
+        assert_eq!(
+            translations(&pair_subtitles(&source, &target, &UnicodeTokenizer, &UnicodeTokenizer)),
+            vec![("Ja. Das ist gut.", "Yes, that is good."), ("Danke.", "Thanks.")]
+        );
+    }
+
+    #[test]
+    fn leaves_out_sentences_without_a_counterpart() {
+        let source = timed_subtitles(vec![
+            ("00:00:00,000", "00:00:02,000", "Musik."),
+            ("00:00:03,000", "00:00:05,000", "Hallo."),
+        ]);
+
+        let target = timed_subtitles(vec![("00:00:03,000", "00:00:05,000", "Hello.")]);
+
+        let pairs = pair_subtitles(&source, &target, &UnicodeTokenizer, &UnicodeTokenizer);
+
+        assert_eq!(translations(&pairs), vec![("Hallo.", "Hello.")]);
+    }
+
+    #[test]
+    fn writes_a_cue_per_pair() {
+        let source = timed_subtitles(vec![("00:00:00,000", "00:00:02,000", "Hallo.")]);
+        let target = timed_subtitles(vec![("00:00:00,500", "00:00:02,500", "Hello.")]);
+
+        let pairs = pair_subtitles(&source, &target, &UnicodeTokenizer, &UnicodeTokenizer);
+        let (source_cues, target_cues) = pair_cues(&pairs);
+
+        assert_eq!(source_cues, timed_subtitles(vec![("00:00:00,000", "00:00:02,500", "Hallo.")]));
+        assert_eq!(target_cues, timed_subtitles(vec![("00:00:00,000", "00:00:02,500", "Hello.")]));
+    }
+
+    #[test]
+    fn joins_unspaced_sentences_without_space() {
+        let source = timed_subtitles(vec![
+            ("00:00:00,000", "00:00:02,000", "Ja."),
+            ("00:00:02,000", "00:00:04,000", "Das ist gut."),
+        ]);
+
+        let target = timed_subtitles(vec![
+            ("00:00:00,000", "00:00:02,000", "はい。"),
+            ("00:00:02,000", "00:00:04,000", "それは\nいいです。"),
+        ]);
+
+        let pairs = pair_subtitles(&source, &target, &UnicodeTokenizer, &GraphemeTokenizer);
+
+        assert_eq!(translations(&pairs), vec![("Ja.", "はい。"), ("Das ist gut.", "それはいいです。")]);
+    }
+}
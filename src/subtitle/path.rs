@@ -3,8 +3,20 @@ use std::{env, path::PathBuf};
 
 use anyhow::{anyhow, Context};
 
+use crate::file::read_from_file;
+
+use super::format::SubtitleFormat;
+
+/// Reads the subtitles in the format of the file extension. Files with an unknown
+/// extension are read as SRT.
 pub fn load_from_path(path: &PathBuf) -> Result<Subtitles, anyhow::Error> {
-    return Subtitles::parse_from_file(path, None)
+    let format = SubtitleFormat::from_path(path).unwrap_or(SubtitleFormat::Srt);
+
+    let content = read_from_file(path.to_owned())
+        .context(format!("Failed to read subtitles at [{:?}]", path))?;
+
+    return format
+        .parse(&content)
         .context(format!("Failed to read subtitles at [{:?}]", path));
 }
 
@@ -35,14 +47,10 @@ pub fn build_subtitle_path(
     channel: &Option<String>,
 ) -> Result<PathBuf, anyhow::Error> {
     if let Some(file) = arg_file {
-        if let Some(ext) = file.extension() {
-            if ext == "srt" {
-                return Ok(file);
-            } else {
-                return Err(anyhow!("File must end with an srt extension!"));
-            }
+        if SubtitleFormat::from_path(&file).is_some_and(|format| format.is_writable()) {
+            return Ok(file);
         } else {
-            return Err(anyhow!("File must end with an srt extension!"));
+            return Err(anyhow!("File must end with an srt or vtt extension!"));
         }
     } else if let Some(path) = arg_path {
         return Ok(subtitle_sub_path(path, title, lang, channel));
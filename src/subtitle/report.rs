@@ -0,0 +1,277 @@
+use std::fmt;
+
+use itertools::Itertools;
+use serde::Serialize;
+use srtlib::Subtitles;
+use tabled::Tabled;
+
+use crate::translation::Translation;
+
+use super::extract::ends_sentence;
+
+/// Cues whose ratio is this many times above or below the median ratio are outliers.
+const OUTLIER_FACTOR: f64 = 2.0;
+
+/// Cues with fewer source characters are too short for their ratio to mean anything.
+const MIN_RATIO_CHARS: usize = 10;
+
+/// Something about a translated cue that is worth a second look.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CueFlag {
+    /// The cue received no target words.
+    Empty,
+
+    /// The target is much longer or shorter than is usual for this translation.
+    RatioOutlier,
+
+    /// The source cue starts a sentence but the target cue continues the previous one.
+    StartsMidSentence,
+}
+
+impl fmt::Display for CueFlag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let flag = match self {
+            CueFlag::Empty => "empty",
+            CueFlag::RatioOutlier => "ratio outlier",
+            CueFlag::StartsMidSentence => "starts mid-sentence",
+        };
+
+        write!(f, "{}", flag)
+    }
+}
+
+/// How well the target text of a cue fits its source text.
+#[derive(Debug, Clone, PartialEq, Serialize, Tabled)]
+pub struct CueReport {
+    #[tabled(rename = "#")]
+    pub num: usize,
+
+    #[tabled(rename = "Source")]
+    pub source_text: String,
+
+    #[tabled(rename = "Translated")]
+    pub target_text: String,
+
+    /// Characters in the target divided by characters in the source.
+    #[tabled(rename = "Ratio", display_with = "display_ratio")]
+    pub ratio: f64,
+
+    #[tabled(rename = "Flags", display_with = "display_flags")]
+    pub flags: Vec<CueFlag>,
+}
+
+impl CueReport {
+    pub fn is_flagged(&self) -> bool {
+        !self.flags.is_empty()
+    }
+}
+
+fn display_ratio(ratio: &f64) -> String {
+    format!("{:.2}", ratio)
+}
+
+fn display_flags(flags: &[CueFlag]) -> String {
+    flags.iter().join(", ")
+}
+
+/// Reports every cue of the translated subtitles next to its source cue.
+pub fn alignment_report(source: &Subtitles, target: &Subtitles) -> Vec<CueReport> {
+    report(source.into_iter().zip(target).map(|(s, t)| (s.num, s.text.as_str(), t.text.as_str())))
+}
+
+/// Reports every translated sentence next to its source sentence. Subtitles with one cue
+/// per sentence have no source cue for each translated cue, so their sentences are reported
+/// instead and numbered in order.
+pub fn sentence_report(translations: &[Translation]) -> Vec<CueReport> {
+    report(
+        translations
+            .iter()
+            .filter(|t| !t.source_text.trim().is_empty())
+            .enumerate()
+            .map(|(index, t)| (index + 1, t.source_text.as_str(), t.target_text.as_str())),
+    )
+}
+
+/// Reports the numbered source and target texts and flags the suspicious ones.
+fn report<'a>(texts: impl Iterator<Item = (usize, &'a str, &'a str)>) -> Vec<CueReport> {
+    let mut reports: Vec<CueReport> = texts
+        .map(|(num, source, target)| CueReport {
+            num,
+            source_text: source.to_owned(),
+            target_text: target.to_owned(),
+            ratio: ratio(source, target),
+            flags: Vec::new(),
+        })
+        .collect();
+
+    let median = median_ratio(&reports);
+
+    for index in 0..reports.len() {
+        let mut flags = Vec::new();
+        let report = &reports[index];
+
+        if report.target_text.trim().is_empty() {
+            flags.push(CueFlag::Empty);
+        } else if let Some(median) = median {
+            let long_enough = char_count(&report.source_text) >= MIN_RATIO_CHARS;
+
+            if long_enough
+                && (report.ratio > median * OUTLIER_FACTOR || report.ratio < median / OUTLIER_FACTOR)
+            {
+                flags.push(CueFlag::RatioOutlier);
+            }
+        }
+
+        if index > 0 {
+            let previous = &reports[index - 1];
+
+            if ends_sentence(&previous.source_text)
+                && !previous.target_text.trim().is_empty()
+                && !ends_sentence(&previous.target_text)
+            {
+                flags.push(CueFlag::StartsMidSentence);
+            }
+        }
+
+        reports[index].flags = flags;
+    }
+
+    reports
+}
+
+/// The number of cues with each flag.
+pub fn summary(reports: &[CueReport]) -> Vec<(CueFlag, usize)> {
+    [CueFlag::Empty, CueFlag::RatioOutlier, CueFlag::StartsMidSentence]
+        .into_iter()
+        .map(|flag| (flag, reports.iter().filter(|r| r.flags.contains(&flag)).count()))
+        .collect()
+}
+
+/// The report as comma separated values with a header row.
+pub fn to_csv(reports: &[CueReport]) -> String {
+    let header = "num,source,target,ratio,flags".to_owned();
+
+    let rows = reports.iter().map(|r| {
+        [
+            r.num.to_string(),
+            csv_field(&r.source_text),
+            csv_field(&r.target_text),
+            display_ratio(&r.ratio),
+            csv_field(&display_flags(&r.flags)),
+        ]
+        .join(",")
+    });
+
+    std::iter::once(header).chain(rows).join("\n")
+}
+
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_owned()
+    }
+}
+
+fn ratio(source: &str, target: &str) -> f64 {
+    match char_count(source) {
+        0 => 0.0,
+        source_chars => char_count(target) as f64 / source_chars as f64,
+    }
+}
+
+fn median_ratio(reports: &[CueReport]) -> Option<f64> {
+    let mut ratios: Vec<f64> = reports
+        .iter()
+        .filter(|r| char_count(&r.source_text) >= MIN_RATIO_CHARS && r.ratio > 0.0)
+        .map(|r| r.ratio)
+        .collect();
+
+    if ratios.is_empty() {
+        return None;
+    }
+
+    ratios.sort_by(|a, b| a.total_cmp(b));
+
+    Some(ratios[ratios.len() / 2])
+}
+
+fn char_count(text: &str) -> usize {
+    text.chars().filter(|c| !c.is_whitespace()).count()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::subtitle::util::test_util::subtitles;
+
+    #[test]
+    fn flags_suspicious_cues() {
+        let source = subtitles(vec![
+            "Es geht um Mountainbiker.",
+            "Wer darf hier wie den Wald nutzen?",
+            "Das ist die Frage,",
+            "die alle stellen.",
+        ]);
+        let target = subtitles(vec![
+            "It's about mountain bikers. Who",
+            "is allowed to use the forest here and how?",
+            "",
+            "That is the question everyone asks and asks and asks again.",
+        ]);
+
+        let reports = alignment_report(&source, &target);
+        let flags: Vec<Vec<CueFlag>> = reports.iter().map(|r| r.flags.clone()).collect();
+
+        assert_eq!(
+            flags,
+            vec![
+                vec![],
+                vec![CueFlag::StartsMidSentence],
+                vec![CueFlag::Empty],
+                vec![CueFlag::RatioOutlier],
+            ]
+        );
+
+        assert_eq!(
+            summary(&reports),
+            vec![
+                (CueFlag::Empty, 1),
+                (CueFlag::RatioOutlier, 1),
+                (CueFlag::StartsMidSentence, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_sentences_of_translations() {
+        let translations = vec![
+            Translation::new("Es geht um Mountainbiker.", "It's about mountain bikers."),
+            Translation::new("", ""),
+            Translation::new("Wer darf hier den Wald nutzen?", ""),
+        ];
+
+        let reports = sentence_report(&translations);
+
+        assert_eq!(reports.iter().map(|r| r.num).collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(reports[0].target_text, "It's about mountain bikers.");
+        assert_eq!(reports[1].flags, vec![CueFlag::Empty]);
+    }
+
+    #[test]
+    fn quotes_csv_fields() {
+        let reports = vec![CueReport {
+            num: 1,
+            source_text: "Ja, \"gut\"".to_owned(),
+            target_text: "Yes".to_owned(),
+            ratio: 0.5,
+            flags: vec![CueFlag::Empty, CueFlag::RatioOutlier],
+        }];
+
+        assert_eq!(
+            to_csv(&reports),
+            "num,source,target,ratio,flags\n1,\"Ja, \"\"gut\"\"\",Yes,0.50,\"empty, ratio outlier\""
+        );
+    }
+}
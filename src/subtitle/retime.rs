@@ -0,0 +1,380 @@
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use srtlib::{Subtitle, Subtitles};
+
+use crate::{segmenter::Segmenter, tokenizer::Tokenizer, translation::Translation};
+
+use super::{
+    layout::{rewrap, LayoutOptions},
+    time::{from_millis, to_millis},
+};
+
+/// Limits for the cues of subtitles with one cue per sentence.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetimeOptions {
+    /// The fastest a viewer is expected to read. Sentences shown for too short a time to
+    /// read them at this speed are split over several cues.
+    pub max_chars_per_second: f64,
+
+    /// The longest a single cue should take to read. Sentences that take longer at
+    /// <max_chars_per_second> are split over several cues.
+    pub max_cue_seconds: f64,
+}
+
+impl Default for RetimeOptions {
+    fn default() -> Self {
+        RetimeOptions {
+            max_chars_per_second: 17.0,
+            max_cue_seconds: 7.0,
+        }
+    }
+}
+
+impl RetimeOptions {
+    /// The most characters a single cue may hold.
+    pub fn max_cue_chars(&self) -> usize {
+        ((self.max_chars_per_second * self.max_cue_seconds).floor() as usize).max(1)
+    }
+
+    /// Whether the characters shown for the milliseconds have to be read faster than
+    /// <max_chars_per_second>.
+    pub fn is_too_fast(&self, chars: usize, millis: u64) -> bool {
+        chars as f64 * 1000.0 > self.max_chars_per_second * millis as f64
+    }
+
+    /// The most characters that can be read in the milliseconds.
+    fn readable_chars(&self, millis: u64) -> usize {
+        (self.max_chars_per_second * millis as f64 / 1000.0).floor() as usize
+    }
+}
+
+/// Creates a cue for every translation instead of splitting the translations over the
+/// subtitles. A cue starts when the first source word of its sentence is spoken and ends
+/// with the last one. When a subtitle holds the end of one sentence and the start of the
+/// next its time is shared by word count. Sentences read faster than the maximum reading
+/// speed, longer than the maximum cue length or too long to fit the layout are split into
+/// cues that share the time of the sentence by character count. A sentence read too fast is
+/// split into parts of what can be read in its time, but no part is made shorter than a
+/// line of the layout.
+/// The text of every cue is wrapped to the layout. The words of the subtitles and the
+/// source text are counted with the tokenizer.
+///
+/// Word times, such as those of auto-generated YouTube captions, hold the times the words of
+/// every subtitle are spoken. Sentences then start and end with their words instead of
+/// sharing the time of a subtitle by word count. Subtitles whose words no longer match
+/// their times, for example after cleaning, fall back to word count.
+pub fn retime_by_sentence(
+    subtitles: &Subtitles,
+    translations: &[Translation],
+    word_times: Option<&[Vec<u64>]>,
+    options: &RetimeOptions,
+    layout: &LayoutOptions,
+    tokenizer: &dyn Tokenizer,
+    segmenter: &dyn Segmenter,
+) -> Result<Subtitles, anyhow::Error> {
+    let words = SubtitleWords::new(subtitles, word_times, tokenizer);
+
+    let source_total: usize = translations
+        .iter()
+        .map(|t| tokenizer.words(&t.source_text).len())
+        .sum();
+
+    if source_total != words.total() {
+        return Err(anyhow!(
+            "The translations have {} source words but the subtitles have {}",
+            source_total,
+            words.total()
+        ));
+    }
+
+    let layout_chars = layout.max_chars_per_line.max(1) * layout.max_lines.max(1);
+    let max_cue_chars = options.max_cue_chars().min(layout_chars);
+
+    let mut cues = Vec::new();
+    let mut position = 0;
+
+    for translation in translations {
+        let count = tokenizer.words(&translation.source_text).len();
+
+        if count == 0 {
+            continue;
+        }
+
+        let start = words.start_of(position);
+        let end = words.end_of(position + count).max(start);
+        position += count;
+
+        let chars = translation.target_text.trim().chars().count();
+        let mut max_chars = max_cue_chars;
+
+        if options.is_too_fast(chars, end - start) {
+            let readable = options.readable_chars(end - start);
+            max_chars = max_chars.min(readable.max(layout.max_chars_per_line.max(1)));
+        }
+
+        let split = LayoutOptions {
+            max_chars_per_line: max_chars,
+            max_lines: usize::MAX,
+        };
+
+        let text = rewrap(&translation.target_text, &split, segmenter);
+
+        if text.is_empty() {
+            continue;
+        }
+
+        let parts: Vec<&str> = text.lines().collect();
+        let chars: Vec<u64> = parts.iter().map(|p| p.chars().count() as u64).collect();
+        let total: u64 = chars.iter().sum::<u64>().max(1);
+
+        let mut seen = 0;
+        let mut part_start = start;
+
+        for (part, part_chars) in parts.into_iter().zip(chars) {
+            seen += part_chars;
+            let part_end = start + (end - start) * seen / total;
+
+            cues.push(Subtitle::new(
+                cues.len() + 1,
+                from_millis(part_start),
+                from_millis(part_end),
+                rewrap(part, layout, segmenter),
+            ));
+
+            part_start = part_end;
+        }
+    }
+
+    Ok(Subtitles::new_from_vec(cues))
+}
+
+/// The number of words of every subtitle and the times they are shown.
+struct SubtitleWords {
+    /// The index of the first word, the number of words, the start and the end of each
+    /// subtitle with words.
+    cues: Vec<(usize, usize, u64, u64)>,
+
+    /// The times the words of each subtitle with words are spoken if known.
+    times: Vec<Option<Vec<u64>>>,
+}
+
+impl SubtitleWords {
+    fn new(subtitles: &Subtitles, word_times: Option<&[Vec<u64>]>, tokenizer: &dyn Tokenizer) -> Self {
+        let mut first = 0;
+        let mut cues = Vec::new();
+        let mut times = Vec::new();
+
+        for (index, subtitle) in subtitles.into_iter().enumerate() {
+            let count = tokenizer.words(&subtitle.text).len();
+
+            if count > 0 {
+                cues.push((first, count, to_millis(&subtitle.start_time), to_millis(&subtitle.end_time)));
+                times.push(
+                    word_times
+                        .and_then(|word_times| word_times.get(index))
+                        .filter(|word_times| word_times.len() == count)
+                        .cloned(),
+                );
+                first += count;
+            }
+        }
+
+        SubtitleWords { cues, times }
+    }
+
+    fn total(&self) -> usize {
+        self.cues.last().map_or(0, |(first, count, _, _)| first + count)
+    }
+
+    /// The time the word at the position starts being spoken.
+    fn start_of(&self, position: usize) -> u64 {
+        self.time_at(position, |(first, count, _, _)| position < first + count)
+    }
+
+    /// The time the words before the position have all been spoken.
+    fn end_of(&self, position: usize) -> u64 {
+        self.time_at(position, |(first, count, _, _)| position <= first + count)
+    }
+
+    /// The time at the position within the first subtitle accepted by the predicate. When
+    /// the position falls within the subtitle the time the word is spoken is taken, or
+    /// interpolated by word count if it is not known.
+    fn time_at<P: Fn(&&(usize, usize, u64, u64)) -> bool>(&self, position: usize, predicate: P) -> u64 {
+        match self.cues.iter().position(|cue| predicate(&cue)) {
+            Some(index) => {
+                let (first, count, start, end) = self.cues[index];
+
+                if let Some(times) = self.times[index].as_ref().filter(|_| position < first + count) {
+                    return times[position - first].clamp(start, end.max(start));
+                }
+
+                let duration = end.saturating_sub(start);
+                start + duration * (position - first) as u64 / count as u64
+            }
+            None => self.cues.last().map_or(0, |(_, _, _, end)| *end),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        segmenter::WhitespaceSegmenter, subtitle::util::test_util::timed_subtitles, tokenizer::UnicodeTokenizer,
+    };
+
+    fn retime(
+        subtitles: &Subtitles,
+        translations: &[Translation],
+        options: &RetimeOptions,
+    ) -> Result<Subtitles, anyhow::Error> {
+        retime_by_sentence(
+            subtitles,
+            translations,
+            None,
+            options,
+            &LayoutOptions::default(),
+            &UnicodeTokenizer,
+            &WhitespaceSegmenter,
+        )
+    }
+
+    fn times(subtitles: &Subtitles) -> Vec<(String, String, String)> {
+        subtitles
+            .into_iter()
+            .map(|s| (s.start_time.to_string(), s.end_time.to_string(), s.text.to_owned()))
+            .collect()
+    }
+
+    #[test]
+    fn one_cue_per_sentence() {
+        let subtitles = timed_subtitles(vec![
+            ("00:00:01,000", "00:00:03,000", "Es geht um\nMountainbiker."),
+            ("00:00:03,500", "00:00:05,000", "Wer darf hier"),
+            ("00:00:05,000", "00:00:07,000", "den Wald nutzen?"),
+        ]);
+
+        let translations = vec![
+            Translation::new("Es geht um Mountainbiker.", "It's about mountain bikers."),
+            Translation::new("Wer darf hier den Wald nutzen?", "Who may use the forest here?"),
+        ];
+
+        let retimed = retime(&subtitles, &translations, &RetimeOptions::default()).unwrap();
+
+        assert_eq!(
+            times(&retimed),
+            vec![
+                ("00:00:01,000".to_owned(), "00:00:03,000".to_owned(), "It's about mountain bikers.".to_owned()),
+                ("00:00:03,500".to_owned(), "00:00:07,000".to_owned(), "Who may use the forest here?".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn shares_a_subtitle_between_sentences_by_word_count() {
+        let subtitles = timed_subtitles(vec![("00:00:00,000", "00:00:04,000", "Ja. Das ist gut.")]);
+
+        let translations = vec![
+            Translation::new("Ja.", "Yes."),
+            Translation::new("Das ist gut.", "That is good."),
+        ];
+
+        let retimed = retime(&subtitles, &translations, &RetimeOptions::default()).unwrap();
+
+        assert_eq!(
+            times(&retimed),
+            vec![
+                ("00:00:00,000".to_owned(), "00:00:01,000".to_owned(), "Yes.".to_owned()),
+                ("00:00:01,000".to_owned(), "00:00:04,000".to_owned(), "That is good.".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn splits_sentences_too_long_to_read() {
+        let subtitles = timed_subtitles(vec![("00:00:00,000", "00:00:06,000", "Eins zwei drei vier")]);
+
+        let translations = vec![Translation::new("Eins zwei drei vier", "aaaa bbbb cccc dddd")];
+
+        let options = RetimeOptions {
+            max_chars_per_second: 5.0,
+            max_cue_seconds: 2.0,
+        };
+
+        let retimed = retime(&subtitles, &translations, &options).unwrap();
+
+        assert_eq!(
+            times(&retimed),
+            vec![
+                ("00:00:00,000".to_owned(), "00:00:03,000".to_owned(), "aaaa bbbb".to_owned()),
+                ("00:00:03,000".to_owned(), "00:00:06,000".to_owned(), "cccc dddd".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn splits_sentences_shown_too_short_to_read() {
+        let subtitles = timed_subtitles(vec![("00:00:00,000", "00:00:02,000", "Eins zwei drei")]);
+
+        let translations = vec![Translation::new(
+            "Eins zwei drei",
+            "This sentence is far too long to read in only two seconds.",
+        )];
+
+        // The sentence fits the cue length and the layout but is read at 29 characters a second.
+        let retimed = retime(&subtitles, &translations, &RetimeOptions::default()).unwrap();
+
+        assert_eq!(
+            times(&retimed),
+            vec![
+                ("00:00:00,000".to_owned(), "00:00:01,017".to_owned(), "This sentence is far too long".to_owned()),
+                ("00:00:01,017".to_owned(), "00:00:02,000".to_owned(), "to read in only two seconds.".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn shares_a_subtitle_between_sentences_by_word_times() {
+        let subtitles = timed_subtitles(vec![
+            ("00:00:00,000", "00:00:04,000", "Ja. Das ist gut."),
+            ("00:00:04,000", "00:00:06,000", "Nein."),
+        ]);
+
+        let translations = vec![
+            Translation::new("Ja.", "Yes."),
+            Translation::new("Das ist gut.", "That is good."),
+            Translation::new("Nein.", "No."),
+        ];
+
+        let word_times = vec![vec![0, 2500, 3000, 3500], vec![4500, 5000]];
+
+        let retimed = retime_by_sentence(
+            &subtitles,
+            &translations,
+            Some(&word_times),
+            &RetimeOptions::default(),
+            &LayoutOptions::default(),
+            &UnicodeTokenizer,
+            &WhitespaceSegmenter,
+        )
+        .unwrap();
+
+        assert_eq!(
+            times(&retimed),
+            vec![
+                ("00:00:00,000".to_owned(), "00:00:02,500".to_owned(), "Yes.".to_owned()),
+                ("00:00:02,500".to_owned(), "00:00:04,000".to_owned(), "That is good.".to_owned()),
+                ("00:00:04,000".to_owned(), "00:00:06,000".to_owned(), "No.".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn mismatched_word_counts_are_an_error() {
+        let subtitles = timed_subtitles(vec![("00:00:00,000", "00:00:01,000", "Eins zwei")]);
+        let translations = vec![Translation::new("Eins", "One")];
+
+        assert!(retime(&subtitles, &translations, &RetimeOptions::default()).is_err());
+    }
+}
@@ -0,0 +1,35 @@
+use srtlib::Timestamp;
+
+/// The timestamp as milliseconds from the start of the video.
+pub fn to_millis(timestamp: &Timestamp) -> u64 {
+    let (hours, minutes, seconds, millis) = timestamp.get();
+
+    return ((hours as u64 * 60 + minutes as u64) * 60 + seconds as u64) * 1000 + millis as u64;
+}
+
+/// The timestamp at the given milliseconds from the start of the video. Times past the
+/// largest timestamp are capped to it.
+pub fn from_millis(millis: u64) -> Timestamp {
+    let seconds = millis / 1000;
+    let minutes = seconds / 60;
+    let hours = (minutes / 60).min(u8::MAX as u64);
+
+    if hours == u8::MAX as u64 {
+        return Timestamp::new(u8::MAX, 59, 59, 999);
+    }
+
+    return Timestamp::new(hours as u8, (minutes % 60) as u8, (seconds % 60) as u8, (millis % 1000) as u16);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trips_milliseconds() {
+        let timestamp = Timestamp::parse("01:02:03,456").unwrap();
+
+        assert_eq!(to_millis(&timestamp), 3_723_456);
+        assert_eq!(from_millis(3_723_456), timestamp);
+    }
+}
@@ -0,0 +1,301 @@
+use anyhow::{anyhow, Context};
+use serde::Deserialize;
+use srtlib::{Subtitle, Subtitles, Timestamp};
+
+use crate::tokenizer::Tokenizer;
+
+use super::time::{from_millis, to_millis};
+
+/// The times in milliseconds that the words of each subtitle are spoken.
+pub type WordTimes = Vec<Vec<u64>>;
+
+/// The timed text of YouTube captions in the json3 or srv3 format. Auto-generated captions
+/// give the time every word is spoken, which is kept on the words of the cues.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimedText {
+    pub cues: Vec<TimedCue>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimedCue {
+    pub start_time: Timestamp,
+    pub end_time: Timestamp,
+
+    /// The pieces of text the cue is made of. Captions typed by hand have a single word
+    /// holding all of their text.
+    pub words: Vec<TimedWord>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimedWord {
+    /// The word with the space in front of it, so that joining the words of a cue gives
+    /// back its text.
+    pub text: String,
+
+    /// When the word is spoken.
+    pub start_time: Timestamp,
+}
+
+impl TimedCue {
+    pub fn text(&self) -> String {
+        self.words.iter().map(|w| w.text.as_str()).collect::<String>().trim().to_owned()
+    }
+}
+
+#[derive(Deserialize)]
+struct Json3 {
+    #[serde(default)]
+    events: Vec<Json3Event>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Json3Event {
+    #[serde(default)]
+    t_start_ms: u64,
+
+    #[serde(default)]
+    d_duration_ms: u64,
+
+    /// Window definitions and the like have no segments.
+    #[serde(default)]
+    segs: Vec<Json3Segment>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Json3Segment {
+    #[serde(default)]
+    utf8: String,
+
+    /// The offset from the start of the event.
+    #[serde(default)]
+    t_offset_ms: u64,
+}
+
+impl TimedText {
+    pub fn parse_json3(input: &str) -> Result<TimedText, anyhow::Error> {
+        let json3: Json3 = serde_json::from_str(input).context("Malformed json3 captions")?;
+
+        let cues = json3
+            .events
+            .into_iter()
+            .map(|event| {
+                let words = event
+                    .segs
+                    .into_iter()
+                    .map(|seg| TimedWord {
+                        text: seg.utf8,
+                        start_time: from_millis(event.t_start_ms + seg.t_offset_ms),
+                    })
+                    .collect();
+
+                TimedCue {
+                    start_time: from_millis(event.t_start_ms),
+                    end_time: from_millis(event.t_start_ms + event.d_duration_ms),
+                    words,
+                }
+            })
+            .collect();
+
+        Ok(TimedText::new(cues))
+    }
+
+    pub fn parse_srv3(input: &str) -> Result<TimedText, anyhow::Error> {
+        let document = roxmltree::Document::parse(input).context("Malformed srv3 captions")?;
+
+        let root = document.root_element();
+
+        if !root.has_tag_name("timedtext") {
+            return Err(anyhow!("srv3 captions must have a timedtext root element"));
+        }
+
+        let paragraphs = root
+            .children()
+            .filter(|node| node.has_tag_name("body"))
+            .flat_map(|body| body.children())
+            .filter(|node| node.has_tag_name("p"));
+
+        let mut cues = Vec::new();
+
+        for p in paragraphs {
+            let start = millis_attribute(&p, "t")?.unwrap_or(0);
+            let duration = millis_attribute(&p, "d")?.unwrap_or(0);
+
+            let mut words = Vec::new();
+
+            for node in p.children() {
+                let (text, offset) = match node.tag_name().name() {
+                    "s" => (node_text(&node), millis_attribute(&node, "t")?.unwrap_or(0)),
+                    "br" => ("\n".to_owned(), 0),
+                    _ if node.is_text() => (node.text().unwrap_or("").to_owned(), 0),
+                    _ => continue,
+                };
+
+                words.push(TimedWord {
+                    text,
+                    start_time: from_millis(start + offset),
+                });
+            }
+
+            cues.push(TimedCue {
+                start_time: from_millis(start),
+                end_time: from_millis(start + duration),
+                words,
+            });
+        }
+
+        Ok(TimedText::new(cues))
+    }
+
+    /// Drops the cues without text, such as the line breaks auto-generated captions append
+    /// to their cues.
+    fn new(cues: Vec<TimedCue>) -> TimedText {
+        let cues = cues.into_iter().filter(|cue| !cue.text().is_empty()).collect();
+
+        TimedText { cues }
+    }
+
+    /// The cues as subtitles. Auto-generated captions keep a cue on screen until the next
+    /// but one starts, so cues are cut off where the next cue starts.
+    pub fn to_subtitles(&self) -> Subtitles {
+        let subtitles = self
+            .cues
+            .iter()
+            .enumerate()
+            .map(|(index, cue)| {
+                Subtitle::new(index + 1, cue.start_time, from_millis(self.end_of(index)), cue.text())
+            })
+            .collect();
+
+        Subtitles::new_from_vec(subtitles)
+    }
+
+    /// The times in milliseconds that the words of every cue of to_subtitles are spoken,
+    /// with the words split by the tokenizer. A timed word that holds several words, such
+    /// as the text of a caption typed by hand, shares its time between them by word count.
+    pub fn word_times(&self, tokenizer: &dyn Tokenizer) -> WordTimes {
+        self.cues
+            .iter()
+            .enumerate()
+            .map(|(index, cue)| {
+                let cue_end = self.end_of(index);
+                let mut times = Vec::new();
+
+                for (position, word) in cue.words.iter().enumerate() {
+                    let start = to_millis(&word.start_time);
+                    let end = cue
+                        .words
+                        .get(position + 1)
+                        .map_or(cue_end, |next| to_millis(&next.start_time))
+                        .max(start);
+
+                    let count = tokenizer.words(&word.text).len() as u64;
+
+                    times.extend((0..count).map(|i| start + (end - start) * i / count));
+                }
+
+                times
+            })
+            .collect()
+    }
+
+    /// The end of the cue cut off where the next cue starts.
+    fn end_of(&self, index: usize) -> u64 {
+        let end = to_millis(&self.cues[index].end_time);
+
+        match self.cues.get(index + 1) {
+            Some(next) => end.min(to_millis(&next.start_time)),
+            None => end,
+        }
+    }
+}
+
+fn millis_attribute(node: &roxmltree::Node, name: &str) -> Result<Option<u64>, anyhow::Error> {
+    node.attribute(name)
+        .map(|value| {
+            value
+                .parse::<u64>()
+                .map_err(|_| anyhow!("Malformed time [{}] in attribute {} of srv3 captions", value, name))
+        })
+        .transpose()
+}
+
+fn node_text(node: &roxmltree::Node) -> String {
+    node.descendants()
+        .filter(|n| n.is_text())
+        .filter_map(|n| n.text())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{subtitle::util::test_util::timed_subtitles, tokenizer::UnicodeTokenizer};
+
+    static JSON3: &str = r#"{
+        "wireMagic": "pb3",
+        "events": [
+            {"tStartMs": 0, "dDurationMs": 4000, "id": 1, "wpWinPosId": 1},
+            {"tStartMs": 1000, "dDurationMs": 3000, "wWinId": 1, "segs": [
+                {"utf8": "es", "acAsrConf": 0},
+                {"utf8": " geht", "tOffsetMs": 320, "acAsrConf": 0},
+                {"utf8": " los", "tOffsetMs": 800, "acAsrConf": 0}
+            ]},
+            {"tStartMs": 2500, "dDurationMs": 1500, "wWinId": 1, "aAppend": 1, "segs": [{"utf8": "\n"}]},
+            {"tStartMs": 2500, "dDurationMs": 2000, "wWinId": 1, "segs": [
+                {"utf8": "jetzt", "acAsrConf": 0}
+            ]}
+        ]
+    }"#;
+
+    static SRV3: &str = r#"<?xml version="1.0" encoding="utf-8" ?>
+        <timedtext format="3">
+        <head><ws id="0"/><wp id="0"/></head>
+        <body>
+        <p t="1000" d="3000" w="1"><s ac="0">es</s><s t="320" ac="0"> geht</s><s t="800" ac="0"> los</s></p>
+        <p t="2500" d="1500" w="1" a="1">
+        </p>
+        <p t="2500" d="2000" w="1"><s ac="0">jetzt</s></p>
+        <p t="5000" d="1000">Tom &amp; Jerry</p>
+        </body>
+        </timedtext>"#;
+
+    #[test]
+    fn parses_word_times_of_json3() {
+        let timed = TimedText::parse_json3(JSON3).unwrap();
+
+        assert_eq!(timed.cues[0].words[1].text, " geht");
+        assert_eq!(timed.word_times(&UnicodeTokenizer), vec![vec![1000, 1320, 1800], vec![2500]]);
+    }
+
+    #[test]
+    fn parses_word_times_of_srv3() {
+        let timed = TimedText::parse_srv3(SRV3).unwrap();
+
+        assert_eq!(
+            timed.word_times(&UnicodeTokenizer),
+            vec![vec![1000, 1320, 1800], vec![2500], vec![5000, 5333, 5666]]
+        );
+    }
+
+    #[test]
+    fn cuts_overlapping_cues_off_at_the_next_cue() {
+        let subtitles = TimedText::parse_srv3(SRV3).unwrap().to_subtitles();
+
+        assert_eq!(
+            subtitles,
+            timed_subtitles(vec![
+                ("00:00:01,000", "00:00:02,500", "es geht los"),
+                ("00:00:02,500", "00:00:04,500", "jetzt"),
+                ("00:00:05,000", "00:00:06,000", "Tom & Jerry"),
+            ])
+        );
+    }
+
+    #[test]
+    fn rejects_other_xml() {
+        assert!(TimedText::parse_srv3("<tt><body/></tt>").is_err());
+        assert!(TimedText::parse_json3("WEBVTT").is_err());
+    }
+}
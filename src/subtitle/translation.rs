@@ -1,9 +1,18 @@
+use std::{collections::VecDeque, iter::Enumerate, slice::Iter};
+
 use fancy_regex::Regex;
-use itertools::Itertools;
 use lazy_static::lazy_static;
 use srtlib::{Subtitle, Subtitles};
+use thiserror::Error;
 
-use crate::{translation::{Translation, VecExt}};
+use crate::{
+    clause::clauses,
+    lexicon::Lexicon,
+    proportional::{ProportionalIter, ProportionError, Weighting},
+    segmenter::{segmenter_for_lang, Segmenter, WhitespaceSegmenter},
+    tokenizer::{tokenizer_for_lang, Tokenizer, UnicodeTokenizer},
+    translation::Translation,
+};
 
 use super::ext::SubtitleExt;
 
@@ -12,60 +21,288 @@ lazy_static!{
     static ref MULTI_SPACE_RE: Regex = Regex::new("[ ]{2,}").unwrap();
 }
 
+/// The reason the words of the subtitles could not be matched with the translations.
+#[derive(Debug, Error, PartialEq)]
+pub enum AlignError {
+    #[error("The word [{actual}] of subtitle {subtitle} did not equal the next source word \
+    [{expected}] of translation {translation}")]
+    TokenMismatch {
+        subtitle: usize,
+        expected: String,
+        actual: String,
+        translation: usize,
+    },
+
+    #[error("The word [{actual}] of subtitle {subtitle} is left after all translations were used")]
+    TranslationsExhausted { subtitle: usize, actual: String },
+
+    #[error("The word [{expected}] of translation {translation} is left after all subtitles were used")]
+    TranslationsRemaining { translation: usize, expected: String },
+
+    #[error("The words of translation {translation} could not be distributed: {source}")]
+    Proportion {
+        translation: usize,
+        source: ProportionError,
+    },
+}
+
+/// How the translations are distributed over the words of the subtitles.
+pub struct AlignOptions {
+    /// Splits the subtitles and the source text into words.
+    pub tokenizer: Box<dyn Tokenizer>,
+
+    /// Splits the target text into the units given to each source word.
+    pub segmenter: Box<dyn Segmenter>,
+
+    /// Distributes the words within clauses when source and target have the same clause
+    /// punctuation. Otherwise the words are distributed over the whole translation.
+    pub clauses: bool,
+
+    /// How much of the target each source word receives.
+    pub weighting: Weighting,
+
+    /// Places the target words next to the source words they translate when it knows
+    /// enough of them. Otherwise the weighting is used.
+    pub lexicon: Option<Lexicon>,
+}
+
+impl Default for AlignOptions {
+    fn default() -> Self {
+        AlignOptions {
+            tokenizer: Box::new(UnicodeTokenizer),
+            segmenter: Box::new(WhitespaceSegmenter),
+            clauses: true,
+            weighting: Weighting::Uniform,
+            lexicon: None,
+        }
+    }
+}
+
+impl AlignOptions {
+    /// The options suited to translations from the source into the target language.
+    pub fn for_langs(source_lang: &str, target_lang: &str) -> Self {
+        AlignOptions {
+            tokenizer: tokenizer_for_lang(source_lang),
+            segmenter: segmenter_for_lang(target_lang),
+            ..Default::default()
+        }
+    }
+}
+
 pub fn translated_subtitles(source_subtitles: &Subtitles, subtitle_text: &str, translated_text: &str) -> Result<Subtitles, anyhow::Error> {
     let translations = 
         Translation::from_source_and_target(subtitle_text, translated_text)?;
 
-    return align(source_subtitles, &translations)
+    return Ok(align(source_subtitles, &translations)?)
 }
 
 pub fn align(
     subtitles: &Subtitles,
     translations: &[Translation]
-) -> Result<Subtitles, anyhow::Error> {
-    let subtitles_iter = subtitles.into_iter();
-    let mut word_iter = translations.proportioned();
+) -> Result<Subtitles, AlignError> {
+    return align_with(subtitles, translations, &AlignOptions::default())
+}
+
+pub fn align_with(
+    subtitles: &Subtitles,
+    translations: &[Translation],
+    options: &AlignOptions,
+) -> Result<Subtitles, AlignError> {
+    let tokenizer = options.tokenizer.as_ref();
+    let segmenter = options.segmenter.as_ref();
+    let mut word_iter = TranslationWords::new(translations, options);
+    let mut translated_subtitles = Vec::new();
 
-    let translated_subtitles = subtitles_iter.map(|subtitle| {
+    for subtitle in subtitles {
         // trace!("Aligning subtitle [{}] with text: {}", subtitle.num, subtitle.text);
-        let subtitle_tokens = subtitle.words_and_spaces();
+        let mut translated_text = String::new();
 
-        let translated_text: String = subtitle_tokens.map(|subtitle_token| {
+        for subtitle_token in subtitle.words_and_spaces(tokenizer) {
             // trace!("Current subtitle token [{}]", subtitle_token);
 
-            if subtitle_token.trim().is_empty() {
+            if tokenizer.is_space(subtitle_token) {
                 // trace!("Empty. Adding value [{}]", subtitle_token);
-                subtitle_token.to_owned()
-            } else if let Some((source_word, target_words)) = word_iter.next() {
+                translated_text.push_str(&whitespace(subtitle_token, segmenter));
+            } else if let Some((translation, source_word, target_words)) = word_iter.next()? {
                 if subtitle_token == source_word {
-                    let target = target_words.iter().join(" ");
-                    // trace!("Adding translation: [{}]", target);
-                    target
+                    translated_text.push_str(&segmenter.join(&target_words));
                 } else {
-                    panic!("The subtitle word [{}] did not equal the next source word [{}] \
-                    from the translation. This is unexpected and should be considered a bug.",
-                    subtitle_token, source_word);
+                    return Err(AlignError::TokenMismatch {
+                        subtitle: subtitle.num,
+                        expected: source_word.to_owned(),
+                        actual: subtitle_token.to_owned(),
+                        translation,
+                    });
                 }
             } else {
-                panic!("The subtitle word is not empty. There are no more translated words.\
-                This should be considered a bug")
+                return Err(AlignError::TranslationsExhausted {
+                    subtitle: subtitle.num,
+                    actual: subtitle_token.to_owned(),
+                });
             }
-        }).collect();
+        }
 
-        // There is a chance that a space is followed by a translation that has no words. In that
-        // case we get two spaces. Let's replace those.
-        let translated_text = MULTI_SPACE_RE.replace(&translated_text, " ");
-
-        // There is a chance that there is a space token followed by the last translation which
-        // is empty. In that case we get an extra space at the end. Get rid of it.
-        let translated_text = translated_text.trim();
+        translated_subtitles.push(Subtitle::new(
+            subtitle.num,
+            subtitle.start_time,
+            subtitle.end_time,
+            tidy_spaces(&translated_text),
+        ));
+    }
 
-        Subtitle::new(subtitle.num, subtitle.start_time, subtitle.end_time, translated_text.to_string())
-    }).collect();
+    // Checks the bookkeeping of the last translation and that no source words are left.
+    if let Some((translation, source_word, _)) = word_iter.next()? {
+        return Err(AlignError::TranslationsRemaining {
+            translation,
+            expected: source_word.to_owned(),
+        });
+    }
 
     Ok(Subtitles::new_from_vec(translated_subtitles))
 }
 
+/// A coarser alignment that can not fail. The translated words are distributed over the
+/// subtitles in proportion to the number of words in each subtitle, ignoring where one
+/// translation ends and the next begins.
+pub fn align_by_word_count(
+    subtitles: &Subtitles,
+    translations: &[Translation],
+    options: &AlignOptions,
+) -> Subtitles {
+    let segmenter = options.segmenter.as_ref();
+    let target_words: Vec<&str> = translations
+        .iter()
+        .flat_map(|t| segmenter.segment(&t.target_text))
+        .collect();
+
+    let source_counts: Vec<usize> = subtitles
+        .into_iter()
+        .map(|s| options.tokenizer.words(&s.text).len())
+        .collect();
+
+    let source_total: usize = source_counts.iter().sum();
+
+    let mut source_seen = 0;
+    let mut start = 0;
+
+    let translated_subtitles = subtitles
+        .into_iter()
+        .zip(source_counts)
+        .map(|(subtitle, count)| {
+            source_seen += count;
+
+            let end = match source_total {
+                0 => 0,
+                _ => (source_seen * target_words.len() + source_total / 2) / source_total,
+            };
+
+            let text = segmenter.join(&target_words[start..end]);
+            start = end;
+
+            Subtitle::new(subtitle.num, subtitle.start_time, subtitle.end_time, text)
+        })
+        .collect();
+
+    Subtitles::new_from_vec(translated_subtitles)
+}
+
+/// The white space of the subtitle kept in the target. Only line breaks are kept when the
+/// target does not separate its words with spaces.
+fn whitespace(token: &str, segmenter: &dyn Segmenter) -> String {
+    if segmenter.separator().is_empty() {
+        token.chars().filter(|c| *c == '\n').collect()
+    } else {
+        token.to_owned()
+    }
+}
+
+fn tidy_spaces(translated_text: &str) -> String {
+    // There is a chance that a space is followed by a translation that has no words. In that
+    // case we get two spaces. Let's replace those.
+    let translated_text = MULTI_SPACE_RE.replace(translated_text, " ");
+
+    // There is a chance that there is a space token followed by the last translation which
+    // is empty. In that case we get an extra space at the end. Get rid of it.
+    translated_text.trim().to_string()
+}
+
+/// The index of a translation, one of its source words and the target words given to it.
+type TranslationWord<'a> = (usize, &'a str, Vec<&'a str>);
+
+/// Iterates the words of the translations proportionally while keeping track of which
+/// translation they belong to. Each translation is split into clauses first if enabled.
+struct TranslationWords<'a, 's> {
+    translations: Enumerate<Iter<'a, Translation>>,
+    options: &'s AlignOptions,
+    translation: usize,
+    clauses: VecDeque<ProportionalIter<'a>>,
+}
+
+impl<'a, 's> TranslationWords<'a, 's> {
+    fn new(translations: &'a [Translation], options: &'s AlignOptions) -> Self {
+        TranslationWords {
+            translations: translations.iter().enumerate(),
+            options,
+            translation: 0,
+            clauses: VecDeque::new(),
+        }
+    }
+
+    /// Returns the index of the translation, the next source word and its target words.
+    fn next(&mut self) -> Result<Option<TranslationWord<'a>>, AlignError> {
+        loop {
+            if let Some(words) = self.clauses.front_mut() {
+                if let Some((source_word, target_words)) = words.next() {
+                    return Ok(Some((self.translation, source_word, target_words)));
+                }
+
+                words.finish().map_err(|source| AlignError::Proportion {
+                    translation: self.translation,
+                    source,
+                })?;
+
+                self.clauses.pop_front();
+                continue;
+            }
+
+            match self.translations.next() {
+                Some((index, t)) => {
+                    self.translation = index;
+                    self.clauses = self.split(t);
+                }
+                None => return Ok(None),
+            }
+        }
+    }
+
+    fn split(&self, translation: &'a Translation) -> VecDeque<ProportionalIter<'a>> {
+        let source = self.options.tokenizer.words(&translation.source_text);
+        let target = self.options.segmenter.segment(&translation.target_text);
+
+        if self.options.clauses {
+            clauses(source, target)
+                .into_iter()
+                .map(|(source, target)| self.distribute(source, target))
+                .collect()
+        } else {
+            VecDeque::from([self.distribute(source, target)])
+        }
+    }
+
+    fn distribute(&self, source: Vec<&'a str>, target: Vec<&'a str>) -> ProportionalIter<'a> {
+        let counts = self
+            .options
+            .lexicon
+            .as_ref()
+            .and_then(|lexicon| lexicon.counts(&source, &target));
+
+        match counts {
+            Some(counts) => ProportionalIter::from_counts(source, target, counts),
+            None => ProportionalIter::from_weighted_units(source, target, self.options.weighting),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -99,6 +336,13 @@ mod test {
 
         let aligned = align(&subs, &translations).unwrap();
 
+        assert_eq!(aligned, subtitles(vec![
+            "It's about mountain bikers\nand the question:",
+            "Who is allowed to use the forest here and how?"]));
+
+        let options = AlignOptions { clauses: false, ..Default::default() };
+        let aligned = align_with(&subs, &translations, &options).unwrap();
+
         assert_eq!(aligned, subtitles(vec![
             "It's about mountain bikers\nand the question: Who",
             "is allowed to use the forest here and how?"]))
@@ -119,4 +363,109 @@ mod test {
 
         assert_eq!(aligned, subtitles(vec!["We asked you, has anyone of you ever catfished?"]));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_zero_width_space() {
+        let translations = vec![Translation::new("Es ist weit weg.", "It is far away.")];
+
+        let subs = subtitles(vec!["Es ist weit\u{200B}weg."]);
+        let aligned = align(&subs, &translations).unwrap();
+
+        assert_eq!(aligned, subtitles(vec!["It is far\u{200B}away."]));
+    }
+
+    #[test]
+    fn test_token_mismatch_is_an_error() {
+        let translations = vec![
+            Translation::new("Source text", "Target text"),
+            Translation::new("Other words", "Andere Wörter"),
+        ];
+        let subs = subtitles(vec!["Source text", "Other word"]);
+
+        assert_eq!(
+            align(&subs, &translations).unwrap_err(),
+            AlignError::TokenMismatch {
+                subtitle: 2,
+                expected: "words".to_owned(),
+                actual: "word".to_owned(),
+                translation: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_exhausted_translations_are_an_error() {
+        let translations = vec![Translation::new("Source text", "Target text")];
+        let subs = subtitles(vec!["Source text", "More"]);
+
+        assert_eq!(
+            align(&subs, &translations).unwrap_err(),
+            AlignError::TranslationsExhausted {
+                subtitle: 2,
+                actual: "More".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_remaining_translations_are_an_error() {
+        let translations = vec![
+            Translation::new("Source text", "Target text"),
+            Translation::new("More words", "Mehr Wörter"),
+        ];
+        let subs = subtitles(vec!["Source text"]);
+
+        assert_eq!(
+            align(&subs, &translations).unwrap_err(),
+            AlignError::TranslationsRemaining {
+                translation: 1,
+                expected: "More".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_empty_source_with_target_is_an_error() {
+        let translations = vec![
+            Translation::new("Source text", "Target text"),
+            Translation::new("", "Stray"),
+        ];
+        let subs = subtitles(vec!["Source text"]);
+
+        assert_eq!(
+            align(&subs, &translations).unwrap_err(),
+            AlignError::Proportion {
+                translation: 1,
+                source: ProportionError::RemainingTargetWords { remaining: 1 },
+            }
+        );
+    }
+
+    #[test]
+    fn test_align_by_word_count() {
+        let translations = vec![
+            Translation::new("Eins zwei drei", "One two three four five six"),
+            Translation::new("vier", "four"),
+        ];
+        let subs = subtitles(vec!["Eins zwei", "drei\nvier"]);
+
+        assert_eq!(
+            align_by_word_count(&subs, &translations, &AlignOptions::default()),
+            subtitles(vec!["One two three four", "five six four"])
+        );
+    }
+
+    #[test]
+    fn test_graphemes_distributed_over_source_words() {
+        let translations = vec![Translation::new(
+            "Wir lernen jetzt Japanisch.",
+            "私たちは今日本語を勉強しています。",
+        )];
+
+        let subs = subtitles(vec!["Wir lernen", "jetzt\nJapanisch."]);
+
+        let aligned = align_with(&subs, &translations, &AlignOptions::for_langs("de", "ja")).unwrap();
+
+        assert_eq!(aligned, subtitles(vec!["私たちは今日本語", "を勉強し\nています。"]));
+    }
+}
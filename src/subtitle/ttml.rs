@@ -0,0 +1,415 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Context};
+use fancy_regex::Regex;
+use lazy_static::lazy_static;
+use srtlib::{Subtitle, Subtitles};
+
+use super::time::from_millis;
+
+/// How many styles referring to other styles are followed.
+const MAX_STYLE_DEPTH: usize = 8;
+
+lazy_static! {
+    static ref OFFSET_TIME: Regex = Regex::new(r"^(\d+(?:\.\d+)?)(h|ms|m|s|f|t)$").unwrap();
+    static ref XML_SPACE: Regex = Regex::new(r"[ \t\r\n]+").unwrap();
+}
+
+/// The frame and tick rates time expressions are counted in. TTML gives them as
+/// attributes of the root element.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct TimeBase {
+    frame_rate: f64,
+    sub_frame_rate: f64,
+    tick_rate: f64,
+}
+
+impl TimeBase {
+    fn from_root(root: &roxmltree::Node) -> Result<TimeBase, anyhow::Error> {
+        let number = |name: &str| -> Result<Option<f64>, anyhow::Error> {
+            attribute(root, name)
+                .map(|value| {
+                    value
+                        .trim()
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|n| *n > 0.0)
+                        .ok_or(anyhow!("Malformed {} [{}]", name, value))
+                })
+                .transpose()
+        };
+
+        let multiplier = match attribute(root, "frameRateMultiplier") {
+            Some(value) => {
+                let parts: Vec<f64> = value.split_whitespace().filter_map(|n| n.parse().ok()).collect();
+
+                match parts.as_slice() {
+                    [numerator, denominator] if *numerator > 0.0 && *denominator > 0.0 => numerator / denominator,
+                    _ => return Err(anyhow!("Malformed frameRateMultiplier [{}]", value)),
+                }
+            }
+            None => 1.0,
+        };
+
+        let frame_rate = number("frameRate")?;
+        let sub_frame_rate = number("subFrameRate")?.unwrap_or(1.0);
+
+        // Without a tick rate ticks are counted in sub frames if a frame rate is given.
+        let tick_rate = match (number("tickRate")?, frame_rate) {
+            (Some(tick_rate), _) => tick_rate,
+            (None, Some(frame_rate)) => frame_rate * sub_frame_rate,
+            (None, None) => 1.0,
+        };
+
+        Ok(TimeBase {
+            frame_rate: frame_rate.unwrap_or(30.0) * multiplier,
+            sub_frame_rate,
+            tick_rate,
+        })
+    }
+
+    /// The milliseconds of a clock time such as 00:01:02.500 or 00:01:02:12 or of an
+    /// offset time such as 62.5s or 1870f or 620000t.
+    fn parse(&self, value: &str) -> Result<u64, anyhow::Error> {
+        let value = value.trim();
+        let malformed = || anyhow!("Malformed time expression [{}]", value);
+
+        if let Some(captures) = OFFSET_TIME.captures(value)? {
+            let count: f64 = captures[1].parse().map_err(|_| malformed())?;
+
+            let seconds = match &captures[2] {
+                "h" => count * 3600.0,
+                "m" => count * 60.0,
+                "s" => count,
+                "ms" => count / 1000.0,
+                "f" => count / self.frame_rate,
+                _ => count / self.tick_rate,
+            };
+
+            return Ok((seconds * 1000.0).round() as u64);
+        }
+
+        let parts: Vec<&str> = value.split(':').collect();
+
+        let number = |part: &str| -> Result<f64, anyhow::Error> {
+            match !part.is_empty() && part.chars().all(|c| c.is_ascii_digit() || c == '.') {
+                true => part.parse::<f64>().map_err(|_| malformed()),
+                false => Err(malformed()),
+            }
+        };
+
+        let seconds = match parts.as_slice() {
+            [hours, minutes, seconds] => number(hours)? * 3600.0 + number(minutes)? * 60.0 + number(seconds)?,
+            [hours, minutes, seconds, frames] => {
+                let (frames, sub_frames) = frames.split_once('.').unwrap_or((frames, "0"));
+
+                number(hours)? * 3600.0
+                    + number(minutes)? * 60.0
+                    + number(seconds)?
+                    + number(frames)? / self.frame_rate
+                    + number(sub_frames)? / (self.frame_rate * self.sub_frame_rate)
+            }
+            _ => return Err(malformed()),
+        };
+
+        Ok((seconds * 1000.0).round() as u64)
+    }
+}
+
+/// Reads TTML and DFXP subtitles. Every <p> becomes a cue, <br/> becomes a line break and
+/// text in italics or bold is kept as <i> and <b>, whether styled inline or through a
+/// style of the <styling> section. Times are taken to be relative to the begin of the
+/// parent element. A <p> that is not timed itself takes its times from the <span>s it
+/// holds or else ends with the first of its ancestors that has an end.
+pub fn parse_ttml(input: &str) -> Result<Subtitles, anyhow::Error> {
+    let document = roxmltree::Document::parse(input).context("Malformed TTML")?;
+    let root = document.root_element();
+
+    if !root.has_tag_name("tt") {
+        return Err(anyhow!("TTML must have a tt root element"));
+    }
+
+    let base = TimeBase::from_root(&root)?;
+
+    let styles: HashMap<&str, roxmltree::Node> = root
+        .descendants()
+        .filter(|node| node.has_tag_name("style"))
+        .filter_map(|node| attribute(&node, "id").map(|id| (id, node)))
+        .collect();
+
+    let mut cues = Vec::new();
+
+    for p in root.descendants().filter(|node| node.has_tag_name("p")) {
+        let text = cue_text(&p, &styles);
+
+        if text.is_empty() {
+            continue;
+        }
+
+        let (begin, end) = paragraph_interval(&p, &base)?;
+        let end = end.ok_or(anyhow!("The paragraph [{}] has no end", text))?;
+
+        cues.push((begin, end, text));
+    }
+
+    cues.sort_by_key(|(begin, _, _)| *begin);
+
+    let subtitles = cues
+        .into_iter()
+        .enumerate()
+        .map(|(index, (begin, end, text))| Subtitle::new(index + 1, from_millis(begin), from_millis(end), text))
+        .collect();
+
+    Ok(Subtitles::new_from_vec(subtitles))
+}
+
+/// The begin and end of a paragraph. Paragraphs without times of their own are timed by the
+/// spans they hold, and without an end of their own end with their nearest ancestor.
+fn paragraph_interval(p: &roxmltree::Node, base: &TimeBase) -> Result<(u64, Option<u64>), anyhow::Error> {
+    let (begin, end) = active_interval(p, base)?;
+
+    let spans = p
+        .descendants()
+        .skip(1)
+        .filter(|node| attribute(node, "begin").is_some() || attribute(node, "end").is_some())
+        .map(|span| active_interval(&span, base))
+        .collect::<Result<Vec<(u64, Option<u64>)>, anyhow::Error>>()?;
+
+    let begin = match attribute(p, "begin") {
+        Some(_) => begin,
+        None => spans.iter().map(|(begin, _)| *begin).min().unwrap_or(begin),
+    };
+
+    if end.is_some() {
+        return Ok((begin, end));
+    }
+
+    if let Some(end) = spans.iter().filter_map(|(_, end)| *end).max() {
+        return Ok((begin, Some(end)));
+    }
+
+    for ancestor in p.ancestors().skip(1).filter(|node| node.is_element()) {
+        if let (_, Some(end)) = active_interval(&ancestor, base)? {
+            return Ok((begin, Some(end)));
+        }
+    }
+
+    Ok((begin, None))
+}
+
+/// The begin and, if it has one, the end of the element in milliseconds from the start of
+/// the document.
+fn active_interval(node: &roxmltree::Node, base: &TimeBase) -> Result<(u64, Option<u64>), anyhow::Error> {
+    let parent_begin = match node.parent_element() {
+        Some(parent) => active_interval(&parent, base)?.0,
+        None => 0,
+    };
+
+    let begin = match attribute(node, "begin") {
+        Some(begin) => parent_begin + base.parse(begin)?,
+        None => parent_begin,
+    };
+
+    let end = match (attribute(node, "end"), attribute(node, "dur")) {
+        (Some(end), _) => Some(parent_begin + base.parse(end)?),
+        (None, Some(dur)) => Some(begin + base.parse(dur)?),
+        (None, None) => None,
+    };
+
+    Ok((begin, end))
+}
+
+/// The attribute by its local name, since documents differ in the namespace they place
+/// timing and styling attributes in.
+fn attribute<'a>(node: &roxmltree::Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.attributes().find(|a| a.name() == name).map(|a| a.value())
+}
+
+/// The value of a styling attribute set on the element itself or else by the styles it
+/// refers to, of which the last one setting it wins.
+fn style_value<'a>(
+    node: &roxmltree::Node<'a, '_>,
+    styles: &HashMap<&str, roxmltree::Node<'a, '_>>,
+    name: &str,
+    depth: usize,
+) -> Option<&'a str> {
+    if let Some(value) = attribute(node, name) {
+        return Some(value);
+    }
+
+    // Styles referring to each other in a loop are not followed forever.
+    if depth > MAX_STYLE_DEPTH {
+        return None;
+    }
+
+    attribute(node, "style")?
+        .split_whitespace()
+        .rev()
+        .filter_map(|id| styles.get(id))
+        .find_map(|style| style_value(style, styles, name, depth + 1))
+}
+
+fn cue_text(p: &roxmltree::Node, styles: &HashMap<&str, roxmltree::Node>) -> String {
+    let mut text = String::new();
+    append_styled_text(p, styles, &mut text);
+
+    text.lines().map(|line| line.trim()).filter(|line| !line.is_empty()).collect::<Vec<_>>().join("\n")
+}
+
+/// Appends the text of the element wrapped in the tags of its styles.
+fn append_styled_text(node: &roxmltree::Node, styles: &HashMap<&str, roxmltree::Node>, text: &mut String) {
+    let tags: Vec<&str> = [
+        ("i", style_value(node, styles, "fontStyle", 0) == Some("italic")),
+        ("b", style_value(node, styles, "fontWeight", 0) == Some("bold")),
+    ]
+    .into_iter()
+    .filter(|(_, styled)| *styled)
+    .map(|(tag, _)| tag)
+    .collect();
+
+    for tag in &tags {
+        text.push_str(&format!("<{}>", tag));
+    }
+
+    for child in node.children() {
+        if child.is_text() {
+            text.push_str(&XML_SPACE.replace_all(child.text().unwrap_or(""), " "));
+        } else if child.has_tag_name("br") {
+            text.push('\n');
+        } else if child.is_element() {
+            append_styled_text(&child, styles, text);
+        }
+    }
+
+    for tag in tags.iter().rev() {
+        text.push_str(&format!("</{}>", tag));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::subtitle::util::test_util::timed_subtitles;
+
+    #[test]
+    fn reads_paragraphs_with_line_breaks_and_spans() {
+        let ttml = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <tt xmlns="http://www.w3.org/ns/ttml" xmlns:tts="http://www.w3.org/ns/ttml#styling" xml:lang="de">
+              <body>
+                <div>
+                  <p begin="00:00:01.000" end="00:00:03.500">Es geht um<br/>
+                    <span tts:fontStyle="italic">Mountainbiker</span>.</p>
+                  <p begin="00:00:04.000" dur="2s"><span tts:color="yellow">Wer darf   hier
+                    den <span tts:fontWeight="bold">Wald</span> nutzen?</span></p>
+                </div>
+              </body>
+            </tt>"#;
+
+        assert_eq!(
+            parse_ttml(ttml).unwrap(),
+            timed_subtitles(vec![
+                ("00:00:01,000", "00:00:03,500", "Es geht um\n<i>Mountainbiker</i>."),
+                ("00:00:04,000", "00:00:06,000", "Wer darf hier den <b>Wald</b> nutzen?"),
+            ])
+        );
+    }
+
+    #[test]
+    fn reads_tick_times_of_dfxp() {
+        let dfxp = r#"<tt xmlns="http://www.w3.org/2006/10/ttaf1" xmlns:ttp="http://www.w3.org/2006/10/ttaf1#parameter" ttp:tickRate="10000000">
+              <body><div><p begin="10000000t" end="25000000t">Hallo</p></div></body>
+            </tt>"#;
+
+        assert_eq!(
+            parse_ttml(dfxp).unwrap(),
+            timed_subtitles(vec![("00:00:01,000", "00:00:02,500", "Hallo")])
+        );
+    }
+
+    #[test]
+    fn reads_frame_times() {
+        let base = TimeBase {
+            frame_rate: 25.0,
+            sub_frame_rate: 1.0,
+            tick_rate: 1.0,
+        };
+
+        assert_eq!(base.parse("00:00:01:12").unwrap(), 1480);
+        assert_eq!(base.parse("50f").unwrap(), 2000);
+        assert_eq!(base.parse("1.5m").unwrap(), 90_000);
+        assert!(base.parse("1:2").is_err());
+    }
+
+    #[test]
+    fn reads_ntsc_frame_rates() {
+        let ttml = r#"<tt xmlns="http://www.w3.org/ns/ttml" xmlns:ttp="http://www.w3.org/ns/ttml#parameter" ttp:frameRate="30" ttp:frameRateMultiplier="1000 1001">
+              <body><div><p begin="0f" end="300f">Hallo</p></div></body>
+            </tt>"#;
+
+        let end = parse_ttml(ttml).unwrap().to_vec()[0].end_time;
+
+        assert_eq!(end.get(), (0, 0, 10, 10));
+    }
+
+    #[test]
+    fn resolves_referenced_styles() {
+        let ttml = r#"<tt xmlns="http://www.w3.org/ns/ttml" xmlns:tts="http://www.w3.org/ns/ttml#styling">
+              <head><styling>
+                <style xml:id="base" tts:fontWeight="bold"/>
+                <style xml:id="quote" style="base" tts:fontStyle="italic"/>
+              </styling></head>
+              <body><div>
+                <p begin="1s" end="2s">Er sagt: <span style="quote">Hallo</span></p>
+                <p begin="3s" end="4s" style="base">Tschüss</p>
+              </div></body>
+            </tt>"#;
+
+        assert_eq!(
+            parse_ttml(ttml).unwrap(),
+            timed_subtitles(vec![
+                ("00:00:01,000", "00:00:02,000", "Er sagt: <i><b>Hallo</b></i>"),
+                ("00:00:03,000", "00:00:04,000", "<b>Tschüss</b>"),
+            ])
+        );
+    }
+
+    #[test]
+    fn times_paragraphs_by_their_spans() {
+        let ttml = r#"<tt xmlns="http://www.w3.org/ns/ttml">
+              <body><div>
+                <p><span begin="1s" end="2s">Es geht</span> <span begin="2s" end="3.5s">los</span></p>
+              </div></body>
+            </tt>"#;
+
+        assert_eq!(
+            parse_ttml(ttml).unwrap(),
+            timed_subtitles(vec![("00:00:01,000", "00:00:03,500", "Es geht los")])
+        );
+    }
+
+    #[test]
+    fn inherits_the_end_of_the_parent() {
+        let ttml = r#"<tt xmlns="http://www.w3.org/ns/ttml">
+              <body><div begin="10s" end="15s">
+                <p begin="1s">Hallo</p>
+              </div></body>
+            </tt>"#;
+
+        assert_eq!(
+            parse_ttml(ttml).unwrap(),
+            timed_subtitles(vec![("00:00:11,000", "00:00:15,000", "Hallo")])
+        );
+    }
+
+    #[test]
+    fn adds_begin_of_enclosing_divs() {
+        let ttml = r#"<tt xmlns="http://www.w3.org/ns/ttml">
+              <body><div begin="10s"><p begin="1s" end="2s">Hallo</p></div></body>
+            </tt>"#;
+
+        assert_eq!(
+            parse_ttml(ttml).unwrap(),
+            timed_subtitles(vec![("00:00:11,000", "00:00:12,000", "Hallo")])
+        );
+    }
+}
@@ -19,4 +19,22 @@ pub(crate) mod test_util {
 
         return srtlib::Subtitles::new_from_vec(x)
     }
+
+    /// Subtitles from the start time, end time and text of each cue.
+    pub fn timed_subtitles(cues: Vec<(&str, &str, &str)>) -> Subtitles {
+        let x = cues
+            .into_iter()
+            .enumerate()
+            .map(|(index, (start, end, text))| {
+                Subtitle::new(
+                    index + 1,
+                    Timestamp::parse(start).unwrap(),
+                    Timestamp::parse(end).unwrap(),
+                    text.to_owned(),
+                )
+            })
+            .collect();
+
+        return srtlib::Subtitles::new_from_vec(x)
+    }
 }
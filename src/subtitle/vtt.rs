@@ -0,0 +1,494 @@
+use std::fmt;
+
+use anyhow::{anyhow, Context};
+use srtlib::{Subtitle, Subtitles, Timestamp};
+
+use super::time::from_millis;
+
+/// The formatting tags that SRT understands as well. Every other tag is dropped when
+/// converting to SRT.
+const SRT_TAGS: [&str; 3] = ["b", "i", "u"];
+
+/// A WebVTT file. Blocks other than cues are kept as they are so that writing the file
+/// back out loses nothing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WebVtt {
+    /// Everything after the WEBVTT signature up to the first blank line.
+    pub header: String,
+    pub blocks: Vec<VttBlock>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum VttBlock {
+    /// A comment including its NOTE keyword.
+    Note(String),
+
+    /// A style sheet including its STYLE keyword.
+    Style(String),
+
+    /// A region definition including its REGION keyword.
+    Region(String),
+
+    Cue(VttCue),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VttCue {
+    pub identifier: Option<String>,
+    pub start_time: Timestamp,
+    pub end_time: Timestamp,
+
+    /// Cue settings such as "align:start position:10%" exactly as they were written.
+    pub settings: String,
+
+    /// The text of the cue including its tags and inline timestamps.
+    pub text: String,
+}
+
+impl VttCue {
+    /// The text of the cue without tags and inline timestamps with character references
+    /// resolved.
+    pub fn plain_text(&self) -> String {
+        strip_tags(&self.text, &[]).0
+    }
+
+    /// The inline timestamps of the cue together with the byte offset into the plain text
+    /// where they appear. Auto-generated captions time every word this way.
+    pub fn inline_timestamps(&self) -> Vec<(usize, Timestamp)> {
+        strip_tags(&self.text, &[]).1
+    }
+}
+
+impl WebVtt {
+    pub fn parse(input: &str) -> Result<WebVtt, anyhow::Error> {
+        let input = input.trim_start_matches('\u{FEFF}').replace("\r\n", "\n").replace('\r', "\n");
+        let lines: Vec<&str> = input.lines().collect();
+
+        let signature = lines.first().copied().unwrap_or("");
+        let header = signature
+            .strip_prefix("WEBVTT")
+            .filter(|rest| rest.is_empty() || rest.starts_with([' ', '\t']))
+            .ok_or(anyhow!("A WebVTT file must start with WEBVTT"))?;
+
+        let mut header = header.to_owned();
+        let mut index = 1;
+
+        while index < lines.len() && !lines[index].trim().is_empty() {
+            header.push('\n');
+            header.push_str(lines[index]);
+            index += 1;
+        }
+
+        let mut blocks = Vec::new();
+
+        while index < lines.len() {
+            if lines[index].trim().is_empty() {
+                index += 1;
+                continue;
+            }
+
+            let start = index;
+
+            while index < lines.len() && !lines[index].trim().is_empty() {
+                index += 1;
+            }
+
+            blocks.push(parse_block(&lines[start..index]).context(format!("Invalid WebVTT block at line {}", start + 1))?);
+        }
+
+        Ok(WebVtt { header, blocks })
+    }
+
+    pub fn cues(&self) -> impl Iterator<Item = &VttCue> {
+        self.blocks.iter().filter_map(|block| match block {
+            VttBlock::Cue(cue) => Some(cue),
+            _ => None,
+        })
+    }
+
+    /// The cues as SRT subtitles. Tags SRT does not know and inline timestamps are removed
+    /// and character references are resolved. Cue identifiers, cue settings and the blocks
+    /// other than cues have no place in SRT and are lost. Use with_subtitles to put changed
+    /// subtitles back into the file without losing them.
+    pub fn to_subtitles(&self) -> Subtitles {
+        let subtitles = self
+            .cues()
+            .enumerate()
+            .map(|(index, cue)| {
+                Subtitle::new(index + 1, cue.start_time, cue.end_time, strip_tags(&cue.text, &SRT_TAGS).0)
+            })
+            .collect();
+
+        Subtitles::new_from_vec(subtitles)
+    }
+
+    /// The file with the text of its cues replaced by the text of the subtitles, for example
+    /// after cleaning the subtitles of to_subtitles. Everything else is kept. Fails unless
+    /// there is a subtitle for every cue.
+    pub fn with_subtitles(&self, subtitles: &Subtitles) -> Result<WebVtt, anyhow::Error> {
+        let mut texts = subtitles.into_iter().map(|s| escape(&s.text));
+        let mut vtt = self.clone();
+
+        for block in vtt.blocks.iter_mut() {
+            if let VttBlock::Cue(cue) = block {
+                cue.text = texts
+                    .next()
+                    .ok_or(anyhow!("There are fewer subtitles than WebVTT cues"))?;
+            }
+        }
+
+        if texts.next().is_some() {
+            return Err(anyhow!("There are more subtitles than WebVTT cues"));
+        }
+
+        Ok(vtt)
+    }
+
+    pub fn from_subtitles(subtitles: &Subtitles) -> WebVtt {
+        let blocks = subtitles
+            .into_iter()
+            .map(|s| {
+                VttBlock::Cue(VttCue {
+                    identifier: None,
+                    start_time: s.start_time,
+                    end_time: s.end_time,
+                    settings: String::new(),
+                    text: escape(&s.text),
+                })
+            })
+            .collect();
+
+        WebVtt {
+            header: String::new(),
+            blocks,
+        }
+    }
+}
+
+impl fmt::Display for WebVtt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "WEBVTT{}", self.header)?;
+
+        for block in self.blocks.iter() {
+            writeln!(f)?;
+
+            match block {
+                VttBlock::Note(text) | VttBlock::Style(text) | VttBlock::Region(text) => writeln!(f, "{}", text)?,
+                VttBlock::Cue(cue) => writeln!(f, "{}", cue)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for VttCue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(identifier) = &self.identifier {
+            writeln!(f, "{}", identifier)?;
+        }
+
+        write!(f, "{} --> {}", format_timestamp(&self.start_time), format_timestamp(&self.end_time))?;
+
+        if !self.settings.is_empty() {
+            write!(f, " {}", self.settings)?;
+        }
+
+        write!(f, "\n{}", self.text)
+    }
+}
+
+fn parse_block(lines: &[&str]) -> Result<VttBlock, anyhow::Error> {
+    let text = lines.join("\n");
+
+    match keyword(lines[0]) {
+        Some("NOTE") => return Ok(VttBlock::Note(text)),
+        Some("STYLE") => return Ok(VttBlock::Style(text)),
+        Some("REGION") => return Ok(VttBlock::Region(text)),
+        _ => {}
+    }
+
+    let (identifier, timing) = match lines[0].contains("-->") {
+        true => (None, 0),
+        false => (Some(lines[0].to_owned()), 1),
+    };
+
+    let timing_line = lines
+        .get(timing)
+        .filter(|line| line.contains("-->"))
+        .ok_or(anyhow!("Missing cue timings"))?;
+
+    let (start, rest) = timing_line.split_once("-->").unwrap_or_default();
+    let mut rest = rest.split_whitespace();
+
+    let start_time = parse_timestamp(start.trim())?;
+    let end_time = parse_timestamp(rest.next().unwrap_or(""))?;
+
+    Ok(VttBlock::Cue(VttCue {
+        identifier,
+        start_time,
+        end_time,
+        settings: rest.collect::<Vec<&str>>().join(" "),
+        text: lines[timing + 1..].join("\n"),
+    }))
+}
+
+/// The keyword of a NOTE, STYLE or REGION block.
+fn keyword(line: &str) -> Option<&str> {
+    ["NOTE", "STYLE", "REGION"].into_iter().find(|keyword| {
+        line.strip_prefix(keyword)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with([' ', '\t']))
+    })
+}
+
+/// Parses timestamps of the form mm:ss.ttt or hh:mm:ss.ttt.
+pub fn parse_timestamp(value: &str) -> Result<Timestamp, anyhow::Error> {
+    let malformed = || anyhow!("Malformed timestamp [{}]", value);
+
+    let (time, millis) = value.split_once('.').ok_or_else(malformed)?;
+    let parts: Vec<&str> = time.split(':').collect();
+
+    if parts.len() < 2 || parts.len() > 3 || millis.len() != 3 {
+        return Err(malformed());
+    }
+
+    let number = |part: &str| -> Result<u64, anyhow::Error> {
+        match part.chars().all(|c| c.is_ascii_digit()) && !part.is_empty() {
+            true => part.parse::<u64>().map_err(|_| malformed()),
+            false => Err(malformed()),
+        }
+    };
+
+    let millis = number(millis)?;
+    let mut total = 0;
+
+    for part in parts {
+        total = total * 60 + number(part)?;
+    }
+
+    Ok(from_millis(total * 1000 + millis))
+}
+
+/// Formats the timestamp as hh:mm:ss.ttt
+pub fn format_timestamp(timestamp: &Timestamp) -> String {
+    let (hours, minutes, seconds, millis) = timestamp.get();
+    format!("{:02}:{:02}:{:02}.{:03}", hours, minutes, seconds, millis)
+}
+
+/// Removes the tags of the cue text except the ones to keep and resolves character
+/// references. Returns the text and the inline timestamps with their byte offset into it.
+fn strip_tags(text: &str, keep: &[&str]) -> (String, Vec<(usize, Timestamp)>) {
+    let mut plain = String::new();
+    let mut timestamps = Vec::new();
+    let mut rest = text;
+
+    while let Some(open) = rest.find(['<', '&']) {
+        plain.push_str(&rest[..open]);
+        rest = &rest[open..];
+
+        if rest.starts_with('&') {
+            match rest.find(';').and_then(|end| unescape(&rest[1..end]).map(|c| (end, c))) {
+                Some((end, c)) => {
+                    plain.push(c);
+                    rest = &rest[end + 1..];
+                }
+                None => {
+                    plain.push('&');
+                    rest = &rest[1..];
+                }
+            }
+            continue;
+        }
+
+        let close = match rest.find('>') {
+            Some(close) => close,
+            None => break,
+        };
+
+        let tag = &rest[1..close];
+
+        if let Ok(timestamp) = parse_timestamp(tag) {
+            timestamps.push((plain.len(), timestamp));
+        } else {
+            let closing = tag.starts_with('/');
+            let name = tag
+                .trim_start_matches('/')
+                .split(['.', ' ', '\t'])
+                .next()
+                .unwrap_or("");
+
+            // Classes and annotations are dropped from the tags that are kept.
+            if keep.contains(&name) {
+                plain.push_str(&format!("<{}{}>", if closing { "/" } else { "" }, name));
+            }
+        }
+
+        rest = &rest[close + 1..];
+    }
+
+    plain.push_str(rest);
+
+    (plain, timestamps)
+}
+
+fn unescape(reference: &str) -> Option<char> {
+    match reference {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some('\u{00A0}'),
+        "lrm" => Some('\u{200E}'),
+        "rlm" => Some('\u{200F}'),
+        _ => {
+            let number = reference.strip_prefix('#')?;
+
+            let code = match number.strip_prefix(['x', 'X']) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => number.parse().ok()?,
+            };
+
+            char::from_u32(code)
+        }
+    }
+}
+
+/// Escapes the ampersands and angle brackets of SRT text. The formatting tags of SRT are
+/// kept, so only brackets that do not belong to a tag such as in "a < b" are escaped.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        if c == '<' {
+            if let Some(tag) = srt_tag(rest) {
+                escaped.push_str(tag);
+                rest = &rest[tag.len()..];
+                continue;
+            }
+        }
+
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            c => escaped.push(c),
+        }
+
+        rest = &rest[c.len_utf8()..];
+    }
+
+    escaped
+}
+
+/// The SRT formatting tag the text starts with, such as <i>, </b> or <font color="red">.
+fn srt_tag(text: &str) -> Option<&str> {
+    let end = text.find('>')?;
+    let inner = &text[1..end];
+    let name = inner.strip_prefix('/').unwrap_or(inner).split(char::is_whitespace).next()?;
+
+    let known = SRT_TAGS.contains(&name.to_lowercase().as_str()) || name.eq_ignore_ascii_case("font");
+
+    (known && !inner.contains('<')).then_some(&text[..=end])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{subtitle::clean::clean_subtitles, tokenizer::UnicodeTokenizer};
+
+    const VTT: &str = "\u{FEFF}WEBVTT
+Kind: captions
+Language: de
+
+NOTE Downloaded from
+a video site
+
+STYLE
+::cue { color: white }
+
+intro
+00:01.000 --> 00:00:03.500 align:start position:10%
+Es geht um <b>Mountain</b>biker
+
+00:00:03.500 --> 00:00:05.000
+<v Erzähler>Wer<00:00:04.000><c.yellow> darf</c> hier &amp; dort?
+";
+
+    #[test]
+    fn parses_blocks_and_cues() {
+        let vtt = WebVtt::parse(VTT).unwrap();
+
+        assert_eq!(vtt.header, "\nKind: captions\nLanguage: de");
+        assert_eq!(vtt.blocks.len(), 4);
+        assert_eq!(vtt.blocks[0], VttBlock::Note("NOTE Downloaded from\na video site".to_owned()));
+        assert_eq!(vtt.blocks[1], VttBlock::Style("STYLE\n::cue { color: white }".to_owned()));
+
+        let cues: Vec<&VttCue> = vtt.cues().collect();
+
+        assert_eq!(cues[0].identifier, Some("intro".to_owned()));
+        assert_eq!(cues[0].start_time, Timestamp::new(0, 0, 1, 0));
+        assert_eq!(cues[0].end_time, Timestamp::new(0, 0, 3, 500));
+        assert_eq!(cues[0].settings, "align:start position:10%");
+        assert_eq!(cues[1].identifier, None);
+    }
+
+    #[test]
+    fn finds_inline_timestamps() {
+        let vtt = WebVtt::parse(VTT).unwrap();
+        let cue = vtt.cues().nth(1).unwrap();
+
+        assert_eq!(cue.plain_text(), "Wer darf hier & dort?");
+        assert_eq!(cue.inline_timestamps(), vec![(3, Timestamp::new(0, 0, 4, 0))]);
+    }
+
+    #[test]
+    fn converts_to_subtitles() {
+        let subtitles = WebVtt::parse(VTT).unwrap().to_subtitles();
+
+        assert_eq!(
+            subtitles.to_string().trim(),
+            "1\n00:00:01,000 --> 00:00:03,500\nEs geht um <b>Mountain</b>biker\n\n\
+            2\n00:00:03,500 --> 00:00:05,000\nWer darf hier & dort?"
+        );
+    }
+
+    #[test]
+    fn writes_what_it_reads() {
+        let vtt = WebVtt::parse(VTT).unwrap();
+
+        assert_eq!(WebVtt::parse(&vtt.to_string()).unwrap(), vtt);
+        assert!(vtt.to_string().contains("00:00:01.000 --> 00:00:03.500 align:start position:10%"));
+    }
+
+    #[test]
+    fn keeps_identifiers_settings_and_blocks_with_new_text() {
+        let vtt = WebVtt::parse(VTT).unwrap();
+
+        let mut subtitles = vtt.to_subtitles();
+        clean_subtitles(&mut subtitles, &UnicodeTokenizer);
+
+        let written = vtt.with_subtitles(&subtitles).unwrap().to_string();
+
+        assert!(written.contains("NOTE Downloaded from\na video site"));
+        assert!(written.contains("intro\n00:00:01.000 --> 00:00:03.500 align:start position:10%\nEs geht um Mountainbiker\n"));
+        assert!(written.contains("\nWer darf hier &amp; dort?\n"));
+        assert!(vtt.with_subtitles(&Subtitles::new()).is_err());
+    }
+
+    #[test]
+    fn escapes_brackets_outside_of_tags() {
+        assert_eq!(
+            escape("a < b <i>und</i> <- <font color=\"red\">c > d</font> <x>"),
+            "a &lt; b <i>und</i> &lt;- <font color=\"red\">c &gt; d</font> &lt;x&gt;"
+        );
+        assert_eq!(escape("a < b > c"), "a &lt; b &gt; c");
+    }
+
+    #[test]
+    fn rejects_malformed_timestamps() {
+        assert!(WebVtt::parse("WEBVTT\n\n00:01 --> 00:02.000\nHallo").is_err());
+        assert!(WebVtt::parse("Hallo").is_err());
+    }
+}
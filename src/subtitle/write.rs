@@ -1,19 +1,51 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use anyhow::Context;
 use srtlib::Subtitles;
 
-use crate::path::expand_path;
+use crate::{file::write_to_file, path::expand_path};
 
+use super::{
+    ass::{dual_subtitles, AssOptions},
+    format::SubtitleFormat,
+    vtt::WebVtt,
+};
+
+/// Writes the subtitles in the format of the file extension. Files with an unknown
+/// extension are written as SRT.
 pub fn write_subtitles(path: &PathBuf, subtitles: &Subtitles) -> Result<PathBuf, anyhow::Error> {
+    let format = SubtitleFormat::from_path(path).unwrap_or(SubtitleFormat::Srt);
+
+    write_subtitle_file(path, &format.format(subtitles)?)
+}
+
+/// Writes the source and the target subtitles into one ASS file with a style for each.
+pub fn write_dual_subtitles(
+    path: &Path,
+    source: &Subtitles,
+    target: &Subtitles,
+    options: &AssOptions,
+) -> Result<PathBuf, anyhow::Error> {
+    let script = dual_subtitles(source, target, options)?;
+
+    write_to_file(path, &script).context("Failed to save subtitles")?;
+
+    expand_path(path)
+}
+
+/// Writes the WebVTT file as it is, keeping what SRT subtitles can not hold.
+pub fn write_vtt(path: &PathBuf, vtt: &WebVtt) -> Result<PathBuf, anyhow::Error> {
+    write_subtitle_file(path, &vtt.to_string())
+}
+
+fn write_subtitle_file(path: &PathBuf, content: &str) -> Result<PathBuf, anyhow::Error> {
     let path = expand_path(path).context(format!("Unable to expand path [{:?}]", path))?;
 
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent)
             .context(format!("Failed to create missing paths in path [{:?}]", parent))?;
     }
-    
 
-    subtitles.write_to_file(&path, None).map(|_| path)
+    std::fs::write(&path, content).map(|_| path)
         .context("Failed to save subtitles")
-}
\ No newline at end of file
+}
@@ -0,0 +1,163 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Separates words without being white space. The word joiner and the zero width no-break
+/// space join words instead and so are not space.
+const ZERO_WIDTH_SPACE: char = '\u{200B}';
+
+/// Languages written without spaces between words.
+const UNSPACED_LANGS: [&str; 9] = ["ja", "zh", "yue", "wuu", "th", "lo", "km", "my", "bo"];
+
+/// Splits source text into words and the space between them. Every module that needs to
+/// agree on what a word is uses the same tokenizer so that the words extracted from the
+/// subtitles are the words found in them again when aligning.
+pub trait Tokenizer {
+    /// The words and the runs of space between them. Joining the tokens gives back the
+    /// text.
+    fn tokens<'a>(&self, text: &'a str) -> Vec<&'a str>;
+
+    fn is_space(&self, token: &str) -> bool {
+        token.chars().all(is_space)
+    }
+
+    fn words<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        self.tokens(text)
+            .into_iter()
+            .filter(|t| !self.is_space(t))
+            .collect()
+    }
+
+    /// What is put between words that are joined, such as the lines of a sentence.
+    fn separator(&self) -> &str {
+        " "
+    }
+
+    /// The text without the space at its start and end.
+    fn trim<'a>(&self, text: &'a str) -> &'a str {
+        let tokens = self.tokens(text);
+        let start = tokens.iter().take_while(|t| self.is_space(t)).map(|t| t.len()).sum::<usize>();
+        let end = tokens.iter().rev().take_while(|t| self.is_space(t)).map(|t| t.len()).sum::<usize>();
+
+        &text[start.min(text.len() - end)..text.len() - end]
+    }
+}
+
+/// White space including no-break spaces and the zero width space.
+fn is_space(c: char) -> bool {
+    c.is_whitespace() || c == ZERO_WIDTH_SPACE
+}
+
+/// Splits on space between grapheme clusters so that combining characters stay with the
+/// character they belong to. Suitable for languages that separate their words with spaces.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct UnicodeTokenizer;
+
+impl Tokenizer for UnicodeTokenizer {
+    fn tokens<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        let mut tokens = Vec::new();
+        let mut start = 0;
+        let mut in_space = None;
+
+        for (index, grapheme) in text.grapheme_indices(true) {
+            let space = self.is_space(grapheme);
+
+            if in_space.is_some_and(|s| s != space) {
+                tokens.push(&text[start..index]);
+                start = index;
+            }
+
+            in_space = Some(space);
+        }
+
+        if start < text.len() {
+            tokens.push(&text[start..]);
+        }
+
+        tokens
+    }
+}
+
+/// Makes every grapheme cluster a word. Suitable for languages like Japanese and Chinese
+/// that do not separate their words with spaces.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct GraphemeTokenizer;
+
+impl Tokenizer for GraphemeTokenizer {
+    fn tokens<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        UnicodeTokenizer
+            .tokens(text)
+            .into_iter()
+            .flat_map(|token| match self.is_space(token) {
+                true => vec![token],
+                false => token.graphemes(true).collect(),
+            })
+            .collect()
+    }
+
+    fn separator(&self) -> &str {
+        ""
+    }
+}
+
+/// Whether the language code, such as "ja" or "zh-Hans", is of a language written without
+/// spaces between words.
+pub fn is_unspaced_lang(lang: &str) -> bool {
+    let primary = lang.split(['-', '_']).next().unwrap_or("").to_lowercase();
+
+    UNSPACED_LANGS.contains(&primary.as_str())
+}
+
+/// Picks the tokenizer for a language code such as "de" or "zh-Hans".
+pub fn tokenizer_for_lang(lang: &str) -> Box<dyn Tokenizer> {
+    if is_unspaced_lang(lang) {
+        Box::new(GraphemeTokenizer)
+    } else {
+        Box::new(UnicodeTokenizer)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn splits_words_and_spaces() {
+        assert_eq!(
+            UnicodeTokenizer.tokens("  This is  some text. "),
+            vec!["  ", "This", " ", "is", "  ", "some", " ", "text.", " "]
+        );
+    }
+
+    #[test]
+    fn treats_no_break_and_zero_width_spaces_as_space() {
+        assert_eq!(
+            UnicodeTokenizer.words("10\u{00A0}km weit\u{200B}weg"),
+            vec!["10", "km", "weit", "weg"]
+        );
+    }
+
+    #[test]
+    fn keeps_words_joined_by_word_joiners() {
+        assert_eq!(
+            UnicodeTokenizer.words("weit\u{2060}weg und\u{FEFF}so"),
+            vec!["weit\u{2060}weg", "und\u{FEFF}so"]
+        );
+    }
+
+    #[test]
+    fn trims_space_the_tokenizer_splits_on() {
+        assert_eq!(UnicodeTokenizer.trim("\u{00A0} weit weg\u{200B} "), "weit weg");
+        assert_eq!(UnicodeTokenizer.trim(" \u{200B}"), "");
+    }
+
+    #[test]
+    fn keeps_combining_characters_with_their_base() {
+        assert_eq!(UnicodeTokenizer.words("Cafe\u{0301} au lait"), vec!["Cafe\u{0301}", "au", "lait"]);
+        assert_eq!(GraphemeTokenizer.words("か\u{3099}き"), vec!["か\u{3099}", "き"]);
+    }
+
+    #[test]
+    fn picks_tokenizer_from_language() {
+        assert_eq!(tokenizer_for_lang("ja").words("日本 語"), vec!["日", "本", "語"]);
+        assert_eq!(tokenizer_for_lang("de").words("Guten Tag"), vec!["Guten", "Tag"]);
+    }
+}
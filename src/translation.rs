@@ -2,7 +2,10 @@ use anyhow::anyhow;
 use serde::{Deserialize, Serialize};
 use tabled::Tabled;
 
-use crate::proportional::ProportionalIter;
+use crate::{
+    proportional::{ProportionalIter, Weighting},
+    tokenizer::{Tokenizer, UnicodeTokenizer},
+};
 
 #[derive(Debug, Serialize, Deserialize, Clone, Tabled)]
 pub struct Translation {
@@ -28,38 +31,76 @@ impl Translation {
         let source: Vec<&str> = source.lines().collect();
         let target: Vec<&str> = target.lines().collect();
 
+        return Translation::from_lines(&source, &target);
+    }
+
+    /// Translates each line of source with the given translator.
+    pub fn translate(
+        source: &str,
+        translator: &mut dyn Translator,
+    ) -> Result<Vec<Translation>, anyhow::Error> {
+        let source: Vec<&str> = source.lines().collect();
+        let target = translator.translate(&source)?;
+
+        return Translation::from_lines(&source, &target);
+    }
+
+    /// Pairs each source line with the target line at the same position.
+    pub fn from_lines<S: AsRef<str>>(
+        source: &[&str],
+        target: &[S],
+    ) -> Result<Vec<Translation>, anyhow::Error> {
         if source.len() != target.len() {
             return Err(anyhow!("There are {} lines in source and {} lines in target. Number of lines must be of equal length", source.len(), target.len()));
         } else {
             return Ok(source
-                .into_iter()
-                .zip(target.into_iter())
-                .map(|parts| Translation::new(parts.0, parts.1))
+                .iter()
+                .zip(target)
+                .map(|parts| Translation::new(parts.0, parts.1.as_ref()))
                 .collect());
         }
     }
 }
 
+/// A backend capable of translating lines of source text.
+pub trait Translator {
+    /// Translates each of the source lines. Implementations must return exactly one target
+    /// line for every source line and in the same order.
+    fn translate(&mut self, source: &[&str]) -> Result<Vec<String>, anyhow::Error>;
+}
+
 pub trait VecExt {
-    fn proportioned(&self) -> Box<dyn Iterator<Item = (&str, Vec<&str>)> + '_>;
+    fn proportioned(&self) -> Box<dyn Iterator<Item = (&str, Vec<&str>)> + '_> {
+        self.proportioned_with(Weighting::Uniform)
+    }
+
+    /// Like proportioned but weighs the source words with the given weighting.
+    fn proportioned_with(
+        &self,
+        weighting: Weighting,
+    ) -> Box<dyn Iterator<Item = (&str, Vec<&str>)> + '_>;
 }
 
 impl VecExt for Vec<Translation> {
-    fn proportioned(&self) -> Box<dyn Iterator<Item = (&str, Vec<&str>)> + '_> {
-        Box::new(
-            self.iter().flat_map(|t| {
-                ProportionalIter::new(t.source_text.as_str(), t.target_text.as_str())
-            }),
-        )
+    fn proportioned_with(
+        &self,
+        weighting: Weighting,
+    ) -> Box<dyn Iterator<Item = (&str, Vec<&str>)> + '_> {
+        self.as_slice().proportioned_with(weighting)
     }
 }
 
 impl VecExt for [Translation] {
-    fn proportioned(&self) -> Box<dyn Iterator<Item = (&str, Vec<&str>)> + '_> {
-        Box::new(
-            self.iter().flat_map(|t| {
-                ProportionalIter::new(t.source_text.as_str(), t.target_text.as_str())
-            }),
-        )
+    fn proportioned_with(
+        &self,
+        weighting: Weighting,
+    ) -> Box<dyn Iterator<Item = (&str, Vec<&str>)> + '_> {
+        Box::new(self.iter().flat_map(move |t| {
+            ProportionalIter::from_weighted_units(
+                UnicodeTokenizer.words(&t.source_text),
+                UnicodeTokenizer.words(&t.target_text),
+                weighting,
+            )
+        }))
     }
 }
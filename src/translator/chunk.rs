@@ -0,0 +1,286 @@
+use anyhow::anyhow;
+
+use crate::translation::Translator;
+
+/// Characters that close a sentence when a word ends with them.
+const SENTENCE_END: [char; 4] = ['.', '!', '?', '…'];
+
+/// Characters that may follow the end of a sentence. For example »Wirklich?«
+const CLOSING: [char; 8] = ['"', '\'', '»', '«', '“', '”', ')', ']'];
+
+/// Called before each chunk is translated with the chunk number starting at 1 and the
+/// total number of chunks.
+pub type ProgressFn = Box<dyn FnMut(usize, usize)>;
+
+/// Splits the source into chunks of at most max_chars characters and translates them one
+/// at a time. Lines longer than max_chars are split between sentences and their
+/// translations joined again so the wrapped translator still returns one line per line.
+pub struct ChunkedTranslator {
+    translator: Box<dyn Translator>,
+    max_chars: usize,
+    progress: Option<ProgressFn>,
+
+    /// Joins the translations of the pieces of a split line. A space unless the target
+    /// language is written without spaces between words.
+    separator: String,
+}
+
+impl ChunkedTranslator {
+    pub fn new(translator: Box<dyn Translator>, max_chars: usize) -> Self {
+        ChunkedTranslator {
+            translator,
+            max_chars: max_chars.max(1),
+            progress: None,
+            separator: " ".to_owned(),
+        }
+    }
+
+    pub fn with_progress(mut self, progress: ProgressFn) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    pub fn with_separator(mut self, separator: &str) -> Self {
+        self.separator = separator.to_owned();
+        self
+    }
+}
+
+impl Translator for ChunkedTranslator {
+    fn translate(&mut self, source: &[&str]) -> Result<Vec<String>, anyhow::Error> {
+        // Each piece remembers the line it came from.
+        let pieces: Vec<(usize, &str)> = source
+            .iter()
+            .enumerate()
+            .flat_map(|(index, line)| {
+                split_line(line, self.max_chars)
+                    .into_iter()
+                    .map(move |piece| (index, piece))
+            })
+            .collect();
+
+        let texts: Vec<&str> = pieces.iter().map(|(_, piece)| *piece).collect();
+        let chunks = chunks(&texts, self.max_chars);
+
+        let mut translated_pieces: Vec<String> = Vec::with_capacity(pieces.len());
+
+        for (number, chunk) in chunks.iter().enumerate() {
+            if let Some(progress) = self.progress.as_mut() {
+                progress(number + 1, chunks.len());
+            }
+
+            let translated = self.translator.translate(chunk)?;
+
+            if translated.len() != chunk.len() {
+                return Err(anyhow!(
+                    "There are {} lines in source and {} lines in target of chunk {}. Number of lines must be of equal length",
+                    chunk.len(),
+                    translated.len(),
+                    number + 1
+                ));
+            }
+
+            translated_pieces.extend(translated);
+        }
+
+        let mut target = vec![String::new(); source.len()];
+
+        for ((index, _), translated) in pieces.iter().zip(translated_pieces) {
+            let line = &mut target[*index];
+
+            if line.is_empty() {
+                line.push_str(translated.trim_end());
+            } else if !translated.trim().is_empty() {
+                line.push_str(&self.separator);
+                line.push_str(translated.trim());
+            }
+        }
+
+        Ok(target)
+    }
+}
+
+/// Groups lines into chunks whose lines and newlines add up to at most max_chars characters.
+pub fn chunks<'a>(lines: &[&'a str], max_chars: usize) -> Vec<Vec<&'a str>> {
+    let mut chunks: Vec<Vec<&str>> = Vec::new();
+    let mut chunk: Vec<&str> = Vec::new();
+    let mut chunk_chars = 0;
+
+    for line in lines {
+        let line_chars = line.chars().count();
+        // Every line after the first is preceded by a newline.
+        let needed = if chunk.is_empty() { line_chars } else { line_chars + 1 };
+
+        if !chunk.is_empty() && chunk_chars + needed > max_chars {
+            chunks.push(std::mem::take(&mut chunk));
+            chunk_chars = line_chars;
+        } else {
+            chunk_chars += needed;
+        }
+
+        chunk.push(line);
+    }
+
+    if !chunk.is_empty() {
+        chunks.push(chunk);
+    }
+
+    return chunks;
+}
+
+/// Splits a line longer than max_chars between sentences. A sentence that is still too long
+/// is split between words.
+fn split_line(line: &str, max_chars: usize) -> Vec<&str> {
+    if line.chars().count() <= max_chars {
+        return vec![line];
+    }
+
+    sentences(line)
+        .into_iter()
+        .flat_map(|sentence| pack_words(sentence, max_chars))
+        .collect()
+}
+
+/// Splits text after words that end a sentence. Single letters and numbers followed by a
+/// period such as "Susanne F." or "zum 1." do not end a sentence.
+fn sentences(text: &str) -> Vec<&str> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+
+    for (offset, word) in word_offsets(text) {
+        let stripped = word.trim_end_matches(CLOSING);
+
+        let ends_sentence = stripped.ends_with(SENTENCE_END) && {
+            let body = stripped.trim_end_matches(SENTENCE_END);
+            body.chars().count() > 1 && !body.chars().all(|c| c.is_numeric())
+        };
+
+        if ends_sentence {
+            let end = offset + word.len();
+            sentences.push(text[start..end].trim());
+            start = end;
+        }
+    }
+
+    if !text[start..].trim().is_empty() {
+        sentences.push(text[start..].trim());
+    }
+
+    return sentences;
+}
+
+/// Packs the words of text into pieces of at most max_chars characters. A single word
+/// longer than max_chars becomes a piece of its own.
+fn pack_words(text: &str, max_chars: usize) -> Vec<&str> {
+    if text.chars().count() <= max_chars {
+        return vec![text];
+    }
+
+    let mut pieces = Vec::new();
+    let mut start: Option<usize> = None;
+    let mut end = 0;
+
+    for (offset, word) in word_offsets(text) {
+        let word_end = offset + word.len();
+
+        match start {
+            Some(s) if text[s..word_end].chars().count() > max_chars => {
+                pieces.push(&text[s..end]);
+                start = Some(offset);
+            }
+            None => start = Some(offset),
+            _ => {}
+        }
+
+        end = word_end;
+    }
+
+    if let Some(s) = start {
+        pieces.push(&text[s..end]);
+    }
+
+    return pieces;
+}
+
+/// The words of text together with their byte offset into text.
+fn word_offsets(text: &str) -> Vec<(usize, &str)> {
+    text.split_whitespace()
+        .map(|word| (word.as_ptr() as usize - text.as_ptr() as usize, word))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::*;
+
+    struct Uppercase;
+
+    impl Translator for Uppercase {
+        fn translate(&mut self, source: &[&str]) -> Result<Vec<String>, anyhow::Error> {
+            Ok(source.iter().map(|l| l.to_uppercase()).collect())
+        }
+    }
+
+    #[test]
+    fn chunks_respect_character_limit_including_newlines() {
+        let chunked = chunks(&["aaaa", "bbbb", "cc", "dddddddd"], 9);
+
+        assert_eq!(chunked, vec![vec!["aaaa", "bbbb"], vec!["cc"], vec!["dddddddd"]]);
+    }
+
+    #[test]
+    fn chunks_count_characters_not_bytes() {
+        let chunked = chunks(&["über", "größe"], 10);
+
+        assert_eq!(chunked, vec![vec!["über", "größe"]]);
+    }
+
+    #[test]
+    fn sentences_are_not_split_after_initials_or_numbers() {
+        let split = sentences("Susanne F. stirbt am 1. Mai. Wirklich? »Ja.« Dann");
+
+        assert_eq!(split, vec!["Susanne F. stirbt am 1. Mai.", "Wirklich?", "»Ja.«", "Dann"]);
+    }
+
+    #[test]
+    fn long_line_is_split_between_sentences() {
+        let split = split_line("Eins zwei. Drei vier. Fünf.", 12);
+
+        assert_eq!(split, vec!["Eins zwei.", "Drei vier.", "Fünf."]);
+    }
+
+    #[test]
+    fn long_sentence_is_split_between_words() {
+        let split = split_line("eins zwei drei vier", 10);
+
+        assert_eq!(split, vec!["eins zwei", "drei vier"]);
+    }
+
+    #[test]
+    fn translates_each_chunk_and_joins_split_lines() {
+        let progress = Rc::new(RefCell::new(Vec::new()));
+        let progress_calls = progress.clone();
+
+        let mut translator = ChunkedTranslator::new(Box::new(Uppercase), 12)
+            .with_progress(Box::new(move |n, total| progress_calls.borrow_mut().push((n, total))));
+
+        let translated = translator
+            .translate(&["Eins zwei. Drei vier.", "", "Fünf"])
+            .unwrap();
+
+        // "Eins zwei." | "Drei vier.\n" | "Fünf"
+        assert_eq!(translated, vec!["EINS ZWEI. DREI VIER.", "", "FÜNF"]);
+        assert_eq!(*progress.borrow(), vec![(1, 3), (2, 3), (3, 3)]);
+    }
+
+    #[test]
+    fn joins_split_lines_with_the_separator() {
+        let mut translator = ChunkedTranslator::new(Box::new(Uppercase), 5).with_separator("");
+
+        let translated = translator.translate(&["今日は。 いい天気。"]).unwrap();
+
+        assert_eq!(translated, vec!["今日は。いい天気。"]);
+    }
+}
@@ -0,0 +1,135 @@
+use std::{
+    io::{Read, Write},
+    process::{Child, Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Context};
+use serde::{Deserialize, Serialize};
+
+use crate::translation::Translator;
+
+use super::translate_in_batches;
+
+const DEFAULT_TIMEOUT_SECS: u64 = 300;
+
+/// The <external_command> section of the config.
+///
+/// external_command:
+///   command: "argos-translate --from de --to en"
+///   timeout_secs: 600
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct ExternalCommandConfig {
+    /// A shell command that reads source lines from stdin and writes one translated line to
+    /// stdout for each of them.
+    pub command: String,
+
+    /// How long to wait for the command before killing it.
+    pub timeout_secs: Option<u64>,
+}
+
+/// Translates by piping the source lines through an external command.
+pub struct ExternalCommandTranslator {
+    config: ExternalCommandConfig,
+}
+
+impl ExternalCommandTranslator {
+    pub fn new(config: ExternalCommandConfig) -> Self {
+        ExternalCommandTranslator { config }
+    }
+
+    fn shell_command(&self) -> Command {
+        if cfg!(windows) {
+            let mut command = Command::new("cmd");
+            command.arg("/C").arg(&self.config.command);
+            command
+        } else {
+            let mut command = Command::new("sh");
+            command.arg("-c").arg(&self.config.command);
+            command
+        }
+    }
+
+    fn run(&self, lines: &[&str]) -> Result<Vec<String>, anyhow::Error> {
+        let mut child = self
+            .shell_command()
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context(format!("Failed to run translator command [{}]", self.config.command))?;
+
+        // Write and read on separate threads so a command that produces output before it
+        // has consumed all of its input can not deadlock.
+        let mut stdin = child.stdin.take().ok_or(anyhow!("Failed to open stdin"))?;
+        let input = format!("{}\n", lines.join("\n"));
+        let writer = thread::spawn(move || stdin.write_all(input.as_bytes()));
+
+        let stdout = read_to_string_in_background(child.stdout.take());
+        let stderr = read_to_string_in_background(child.stderr.take());
+
+        let timeout = Duration::from_secs(self.config.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS));
+        let status = wait_with_timeout(&mut child, timeout).context(format!(
+            "Translator command [{}] did not finish",
+            self.config.command
+        ))?;
+
+        // A command that exits without reading its input causes a broken pipe which is
+        // reported through the exit status instead.
+        let _ = writer.join();
+
+        let stdout = stdout.join().unwrap_or_default();
+        let stderr = stderr.join().unwrap_or_default();
+
+        if !status.success() {
+            return Err(anyhow!(
+                "Translator command [{}] failed with {}: {}",
+                self.config.command,
+                status,
+                stderr.trim()
+            ));
+        }
+
+        Ok(stdout.lines().map(|l| l.to_owned()).collect())
+    }
+}
+
+impl Translator for ExternalCommandTranslator {
+    fn translate(&mut self, source: &[&str]) -> Result<Vec<String>, anyhow::Error> {
+        translate_in_batches(source, usize::MAX, usize::MAX, |lines| self.run(lines))
+    }
+}
+
+fn read_to_string_in_background<R: Read + Send + 'static>(
+    reader: Option<R>,
+) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut s = String::new();
+        if let Some(mut reader) = reader {
+            let _ = reader.read_to_string(&mut s);
+        }
+        s
+    })
+}
+
+fn wait_with_timeout(
+    child: &mut Child,
+    timeout: Duration,
+) -> Result<std::process::ExitStatus, anyhow::Error> {
+    let start = Instant::now();
+
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(status);
+        }
+
+        if start.elapsed() >= timeout {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(anyhow!("Timed out after {} seconds", timeout.as_secs()));
+        }
+
+        thread::sleep(Duration::from_millis(20));
+    }
+}
@@ -0,0 +1,150 @@
+use anyhow::{anyhow, Context};
+use reqwest::blocking::Client;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::translation::Translator;
+
+use super::{endpoint_url, translate_in_batches};
+
+/// DeepL accepts at most 50 texts in a single request.
+const MAX_TEXTS_PER_REQUEST: usize = 50;
+
+/// DeepL rejects requests larger than 128 KiB. Leave some room for the rest of the body.
+const MAX_TEXT_BYTES_PER_REQUEST: usize = 120 * 1024;
+
+const FREE_API_URL: &str = "https://api-free.deepl.com";
+const PRO_API_URL: &str = "https://api.deepl.com";
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Formality {
+    Default,
+    More,
+    Less,
+    PreferMore,
+    PreferLess,
+}
+
+/// The <deepl> section of the config.
+///
+/// deepl:
+///   auth_key: "279a2e9d-83b3-c416-7e2d-f721593e42a0:fx"
+///   source_lang: DE
+///   target_lang: EN-US
+///   formality: prefer_less
+///   glossary_id: "def3a26b-3e84-45b3-84ae-0c0aaf3525f7"
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct DeeplConfig {
+    pub auth_key: String,
+
+    /// Overrides the API url. Defaults to the free API for keys ending in ":fx" and the pro
+    /// API otherwise.
+    pub url: Option<Url>,
+
+    /// The source language. DeepL detects it if missing. Required when using a glossary.
+    pub source_lang: Option<String>,
+
+    pub target_lang: String,
+
+    pub formality: Option<Formality>,
+
+    pub glossary_id: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+struct TranslateRequest<'a> {
+    text: &'a [&'a str],
+    target_lang: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source_lang: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    formality: Option<Formality>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    glossary_id: Option<&'a str>,
+    split_sentences: &'a str,
+    preserve_formatting: bool,
+}
+
+#[derive(Deserialize, Debug)]
+struct TranslateResponse {
+    translations: Vec<TranslatedText>,
+}
+
+#[derive(Deserialize, Debug)]
+struct TranslatedText {
+    text: String,
+}
+
+/// Translates using the DeepL API.
+pub struct DeeplTranslator {
+    client: Client,
+    config: DeeplConfig,
+    endpoint: Url,
+}
+
+impl DeeplTranslator {
+    pub fn new(config: DeeplConfig) -> Result<Self, anyhow::Error> {
+        if config.glossary_id.is_some() && config.source_lang.is_none() {
+            return Err(anyhow!("A DeepL glossary requires <source_lang> to be set."));
+        }
+
+        let url = match &config.url {
+            Some(url) => url.to_owned(),
+            None if config.auth_key.ends_with(":fx") => Url::parse(FREE_API_URL)?,
+            None => Url::parse(PRO_API_URL)?,
+        };
+
+        let endpoint = endpoint_url(&url, "v2/translate").context("Invalid DeepL url")?;
+
+        return Ok(DeeplTranslator {
+            client: Client::new(),
+            config,
+            endpoint,
+        });
+    }
+
+    fn translate_batch(&self, batch: &[&str]) -> Result<Vec<String>, anyhow::Error> {
+        let request = TranslateRequest {
+            text: batch,
+            target_lang: &self.config.target_lang,
+            source_lang: self.config.source_lang.as_deref(),
+            formality: self.config.formality,
+            glossary_id: self.config.glossary_id.as_deref(),
+            split_sentences: "nonewlines",
+            preserve_formatting: true,
+        };
+
+        let response = self
+            .client
+            .post(self.endpoint.to_owned())
+            .header("Authorization", format!("DeepL-Auth-Key {}", self.config.auth_key))
+            .json(&request)
+            .send()
+            .context(format!("Failed to send translation request to [{}]", self.endpoint))?;
+
+        let status = response.status();
+
+        if !status.is_success() {
+            let body = response.text().unwrap_or_default();
+            return Err(anyhow!("DeepL responded with [{}]: {}", status, body));
+        }
+
+        let response: TranslateResponse = response
+            .json()
+            .context("Failed to parse DeepL response.")?;
+
+        Ok(response.translations.into_iter().map(|t| t.text).collect())
+    }
+}
+
+impl Translator for DeeplTranslator {
+    fn translate(&mut self, source: &[&str]) -> Result<Vec<String>, anyhow::Error> {
+        translate_in_batches(
+            source,
+            MAX_TEXTS_PER_REQUEST,
+            MAX_TEXT_BYTES_PER_REQUEST,
+            |batch| self.translate_batch(batch),
+        )
+    }
+}
@@ -0,0 +1,121 @@
+use anyhow::{anyhow, Context};
+use reqwest::blocking::Client;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::translation::Translator;
+
+use super::{endpoint_url, translate_in_batches};
+
+/// Keeps requests small enough for instances running with a character limit.
+const MAX_TEXTS_PER_REQUEST: usize = 50;
+const MAX_TEXT_BYTES_PER_REQUEST: usize = 5000;
+
+/// The <libre_translate> section of the config.
+///
+/// libre_translate:
+///   url: "http://192.168.1.20:5000"
+///   api_key: "a4c2f0b1-..."
+///   source_lang: de
+///   target_lang: en
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct LibreTranslateConfig {
+    pub url: Url,
+
+    pub api_key: Option<String>,
+
+    /// The source language. LibreTranslate detects it if missing.
+    pub source_lang: Option<String>,
+
+    pub target_lang: String,
+
+    /// Overrides the maximum number of bytes sent per request.
+    pub max_request_bytes: Option<usize>,
+}
+
+#[derive(Serialize, Debug)]
+struct TranslateRequest<'a> {
+    q: &'a [&'a str],
+    source: &'a str,
+    target: &'a str,
+    format: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    api_key: Option<&'a str>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct TranslateResponse {
+    translated_text: Vec<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ErrorResponse {
+    error: String,
+}
+
+/// Translates using the /translate endpoint of a LibreTranslate server.
+pub struct LibreTranslateTranslator {
+    client: Client,
+    config: LibreTranslateConfig,
+    endpoint: Url,
+}
+
+impl LibreTranslateTranslator {
+    pub fn new(config: LibreTranslateConfig) -> Result<Self, anyhow::Error> {
+        let endpoint = endpoint_url(&config.url, "translate").context("Invalid LibreTranslate url")?;
+
+        return Ok(LibreTranslateTranslator {
+            client: Client::new(),
+            config,
+            endpoint,
+        });
+    }
+
+    fn translate_batch(&self, batch: &[&str]) -> Result<Vec<String>, anyhow::Error> {
+        let request = TranslateRequest {
+            q: batch,
+            source: self.config.source_lang.as_deref().unwrap_or("auto"),
+            target: &self.config.target_lang,
+            format: "text",
+            api_key: self.config.api_key.as_deref(),
+        };
+
+        let response = self
+            .client
+            .post(self.endpoint.to_owned())
+            .json(&request)
+            .send()
+            .context(format!("Failed to send translation request to [{}]", self.endpoint))?;
+
+        let status = response.status();
+
+        if !status.is_success() {
+            let body = response.text().unwrap_or_default();
+            let message = serde_json::from_str::<ErrorResponse>(&body)
+                .map(|e| e.error)
+                .unwrap_or(body);
+
+            return Err(anyhow!("LibreTranslate responded with [{}]: {}", status, message));
+        }
+
+        let response: TranslateResponse = response
+            .json()
+            .context("Failed to parse LibreTranslate response.")?;
+
+        Ok(response.translated_text)
+    }
+}
+
+impl Translator for LibreTranslateTranslator {
+    fn translate(&mut self, source: &[&str]) -> Result<Vec<String>, anyhow::Error> {
+        let max_bytes = self
+            .config
+            .max_request_bytes
+            .unwrap_or(MAX_TEXT_BYTES_PER_REQUEST);
+
+        translate_in_batches(source, MAX_TEXTS_PER_REQUEST, max_bytes, |batch| {
+            self.translate_batch(batch)
+        })
+    }
+}
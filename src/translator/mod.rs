@@ -0,0 +1,125 @@
+use anyhow::{anyhow, Context};
+use url::Url;
+
+pub mod chunk;
+pub mod command;
+pub mod deepl;
+pub mod libre_translate;
+pub mod openai;
+pub mod repair;
+
+/// The url of the endpoint at the path relative to the base url of an API. The base url
+/// is treated as a directory, so that an API served under a path such as
+/// http://host/deepl keeps its path.
+pub fn endpoint_url(base: &Url, path: &str) -> Result<Url, anyhow::Error> {
+    let mut base = base.to_owned();
+
+    // Without a trailing slash join would replace the last segment of the path.
+    if !base.path().ends_with('/') {
+        base.set_path(&format!("{}/", base.path()));
+    }
+
+    base.join(path).context(format!("Invalid url [{}]", base))
+}
+
+/// Translates the non empty source lines in batches. Empty lines are returned as they are
+/// rather than being sent to the translator.
+pub fn translate_in_batches<F>(
+    source: &[&str],
+    max_lines: usize,
+    max_bytes: usize,
+    mut translate_batch: F,
+) -> Result<Vec<String>, anyhow::Error>
+where
+    F: FnMut(&[&str]) -> Result<Vec<String>, anyhow::Error>,
+{
+    let lines: Vec<&str> = source.iter().copied().filter(|l| !l.trim().is_empty()).collect();
+
+    let mut translated = Vec::with_capacity(lines.len());
+
+    for batch in batches(&lines, max_lines, max_bytes) {
+        let batch_translated = translate_batch(&batch)?;
+
+        if batch_translated.len() != batch.len() {
+            return Err(anyhow!(
+                "Sent {} lines to the translator but received {} translations.",
+                batch.len(),
+                batch_translated.len()
+            ));
+        }
+
+        translated.extend(batch_translated);
+    }
+
+    let mut translated = translated.into_iter();
+
+    Ok(source
+        .iter()
+        .map(|l| {
+            if l.trim().is_empty() {
+                l.to_string()
+            } else {
+                translated.next().unwrap_or_default()
+            }
+        })
+        .collect())
+}
+
+/// Splits lines into batches holding at most max_lines lines and max_bytes bytes of text.
+/// A single line larger than max_bytes is placed in a batch of its own.
+pub fn batches<'a>(lines: &[&'a str], max_lines: usize, max_bytes: usize) -> Vec<Vec<&'a str>> {
+    let mut batches: Vec<Vec<&str>> = Vec::new();
+    let mut batch: Vec<&str> = Vec::new();
+    let mut batch_bytes = 0;
+
+    for line in lines {
+        if !batch.is_empty() && (batch.len() == max_lines || batch_bytes + line.len() > max_bytes) {
+            batches.push(std::mem::take(&mut batch));
+            batch_bytes = 0;
+        }
+
+        batch_bytes += line.len();
+        batch.push(line);
+    }
+
+    if !batch.is_empty() {
+        batches.push(batch);
+    }
+
+    return batches;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn endpoint_keeps_path_of_base_url() {
+        let endpoint = |base: &str| endpoint_url(&Url::parse(base).unwrap(), "v2/translate").unwrap();
+
+        assert_eq!(endpoint("https://host/deepl").as_str(), "https://host/deepl/v2/translate");
+        assert_eq!(endpoint("https://host/deepl/").as_str(), "https://host/deepl/v2/translate");
+        assert_eq!(endpoint("https://host").as_str(), "https://host/v2/translate");
+    }
+
+    #[test]
+    fn batches_limited_by_line_count() {
+        let batched = batches(&["a", "b", "c", "d", "e"], 2, 100);
+
+        assert_eq!(batched, vec![vec!["a", "b"], vec!["c", "d"], vec!["e"]]);
+    }
+
+    #[test]
+    fn batches_limited_by_bytes() {
+        let batched = batches(&["aaa", "bbb", "cc", "dddddd", "e"], 10, 6);
+
+        assert_eq!(batched, vec![vec!["aaa", "bbb"], vec!["cc"], vec!["dddddd"], vec!["e"]]);
+    }
+
+    #[test]
+    fn oversized_line_gets_own_batch() {
+        let batched = batches(&["a", "bbbbbbbb", "c"], 10, 4);
+
+        assert_eq!(batched, vec![vec!["a"], vec!["bbbbbbbb"], vec!["c"]]);
+    }
+}
@@ -0,0 +1,424 @@
+use std::{
+    collections::{btree_map::Entry, BTreeMap},
+    time::Duration,
+};
+
+use anyhow::{anyhow, Context};
+use fancy_regex::Regex;
+use itertools::Itertools;
+use lazy_static::lazy_static;
+use log::debug;
+use reqwest::blocking::Client;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::translation::Translator;
+
+use super::endpoint_url;
+
+lazy_static! {
+    // A numbered line of the reply. For example "12: Hello" or "[12] Hello". The number
+    // must be marked so that a line starting with a number such as "20 years later" is not
+    // taken for a numbered line.
+    static ref NUMBERED_LINE_RE: Regex =
+        Regex::new(r"^\s*(?:\[(?P<bracketed>\d+)\]|(?P<num>\d+)[.:)])\s+(?P<text>.*)$").unwrap();
+}
+
+const DEFAULT_BATCH_SIZE: usize = 20;
+const DEFAULT_CONTEXT_LINES: usize = 3;
+const DEFAULT_MAX_RETRIES: usize = 2;
+const DEFAULT_TIMEOUT_SECS: u64 = 300;
+
+/// The <openai> section of the config. Works with any server that provides an OpenAI
+/// compatible /v1/chat/completions endpoint such as llama.cpp server or Ollama.
+///
+/// openai:
+///   url: "http://localhost:11434/v1/"
+///   model: "llama3"
+///   source_lang: German
+///   target_lang: English
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct OpenAiConfig {
+    /// The base url of the API which /chat/completions is appended to.
+    pub url: Url,
+
+    pub api_key: Option<String>,
+
+    pub model: String,
+
+    /// The name of the source language as it should appear in the prompt.
+    pub source_lang: String,
+
+    /// The name of the target language as it should appear in the prompt.
+    pub target_lang: String,
+
+    /// The number of lines sent per request.
+    pub batch_size: Option<usize>,
+
+    /// The number of lines before and after a batch that are sent as context.
+    pub context_lines: Option<usize>,
+
+    /// How many times a batch is sent again when the reply does not contain every line.
+    pub max_retries: Option<usize>,
+
+    pub temperature: Option<f32>,
+
+    pub timeout_secs: Option<u64>,
+}
+
+#[derive(Serialize, Debug)]
+struct ChatRequest<'a> {
+    model: &'a str,
+    messages: Vec<Message>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    stream: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct Message {
+    role: String,
+    content: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct ChatResponse {
+    choices: Vec<Choice>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Choice {
+    message: Message,
+}
+
+/// Translates with a chat model. Lines are numbered in the prompt and the numbers of the
+/// reply are used to match translations to source lines.
+pub struct OpenAiTranslator {
+    client: Client,
+    config: OpenAiConfig,
+    endpoint: Url,
+}
+
+impl OpenAiTranslator {
+    pub fn new(config: OpenAiConfig) -> Result<Self, anyhow::Error> {
+        let endpoint = endpoint_url(&config.url, "chat/completions")?;
+
+        let client = Client::builder()
+            .timeout(Duration::from_secs(
+                config.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS),
+            ))
+            .build()?;
+
+        return Ok(OpenAiTranslator {
+            client,
+            config,
+            endpoint,
+        });
+    }
+
+    fn system_prompt(&self) -> String {
+        format!(
+            "You translate subtitles from {source} to {target}. \
+            The user sends numbered {source} lines in the form \"<number>: <text>\". \
+            Reply with the {target} translation of every line in the same form, one line per \
+            number and nothing else. Never merge, split, skip or reorder lines. \
+            Lines under \"Context\" are only there to help you understand the text and must \
+            not be translated.",
+            source = self.config.source_lang,
+            target = self.config.target_lang
+        )
+    }
+
+    fn user_prompt(before: &[&str], batch: &[&str], after: &[&str]) -> String {
+        let mut prompt = String::new();
+
+        if !before.is_empty() {
+            prompt.push_str(&format!("Context before:\n{}\n\n", before.join("\n")));
+        }
+
+        prompt.push_str(&format!("Translate:\n{}", number_lines(batch)));
+
+        if !after.is_empty() {
+            prompt.push_str(&format!("\n\nContext after:\n{}", after.join("\n")));
+        }
+
+        return prompt;
+    }
+
+    fn complete(&self, user_prompt: String) -> Result<String, anyhow::Error> {
+        let request = ChatRequest {
+            model: &self.config.model,
+            messages: vec![
+                Message {
+                    role: "system".to_owned(),
+                    content: self.system_prompt(),
+                },
+                Message {
+                    role: "user".to_owned(),
+                    content: user_prompt,
+                },
+            ],
+            temperature: self.config.temperature,
+            stream: false,
+        };
+
+        let mut request_builder = self.client.post(self.endpoint.to_owned()).json(&request);
+
+        if let Some(api_key) = &self.config.api_key {
+            request_builder = request_builder.bearer_auth(api_key);
+        }
+
+        let response = request_builder
+            .send()
+            .context(format!("Failed to send translation request to [{}]", self.endpoint))?;
+
+        let status = response.status();
+
+        if !status.is_success() {
+            let body = response.text().unwrap_or_default();
+            return Err(anyhow!("Chat completion responded with [{}]: {}", status, body));
+        }
+
+        let response: ChatResponse = response
+            .json()
+            .context("Failed to parse chat completion response.")?;
+
+        response
+            .choices
+            .into_iter()
+            .next()
+            .map(|c| c.message.content)
+            .ok_or(anyhow!("Chat completion response contained no choices."))
+    }
+
+    /// Translates a batch, sending it again when lines are missing from the reply. Lines
+    /// that are still missing after all retries are translated one at a time.
+    fn translate_batch(
+        &self,
+        before: &[&str],
+        batch: &[&str],
+        after: &[&str],
+    ) -> Result<Vec<String>, anyhow::Error> {
+        let max_retries = self.config.max_retries.unwrap_or(DEFAULT_MAX_RETRIES);
+        let mut translated: BTreeMap<usize, String> = BTreeMap::new();
+
+        for attempt in 0..=max_retries {
+            let reply = self.complete(Self::user_prompt(before, batch, after))?;
+            let parsed = parse_numbered_lines(&reply, batch.len());
+
+            if parsed.len() == batch.len() {
+                return Ok(parsed.into_values().collect());
+            }
+
+            debug!(
+                "Attempt {} returned {} of {} lines.",
+                attempt + 1,
+                parsed.len(),
+                batch.len()
+            );
+
+            for (num, text) in parsed {
+                translated.entry(num).or_insert(text);
+            }
+
+            if translated.len() == batch.len() {
+                return Ok(translated.into_values().collect());
+            }
+        }
+
+        if batch.len() == 1 {
+            return Err(anyhow!(
+                "The model did not return a translation for the line [{}]",
+                batch[0]
+            ));
+        }
+
+        for (index, line) in batch.iter().enumerate() {
+            if let Entry::Vacant(entry) = translated.entry(index + 1) {
+                let text = self.translate_batch(&batch[..index], &[line], &batch[index + 1..])?;
+                entry.insert(text.into_iter().next().unwrap_or_default());
+            }
+        }
+
+        Ok(translated.into_values().collect())
+    }
+}
+
+impl Translator for OpenAiTranslator {
+    fn translate(&mut self, source: &[&str]) -> Result<Vec<String>, anyhow::Error> {
+        let batch_size = self.config.batch_size.unwrap_or(DEFAULT_BATCH_SIZE).max(1);
+        let context_lines = self.config.context_lines.unwrap_or(DEFAULT_CONTEXT_LINES);
+
+        // Empty lines are kept as they are rather than sent to the model.
+        let lines: Vec<&str> = source.iter().copied().filter(|l| !l.trim().is_empty()).collect();
+
+        let mut translated = Vec::with_capacity(lines.len());
+
+        for start in (0..lines.len()).step_by(batch_size) {
+            let end = (start + batch_size).min(lines.len());
+            let before = &lines[start.saturating_sub(context_lines)..start];
+            let after = &lines[end..(end + context_lines).min(lines.len())];
+
+            translated.extend(self.translate_batch(before, &lines[start..end], after)?);
+        }
+
+        let mut translated = translated.into_iter();
+
+        Ok(source
+            .iter()
+            .map(|l| {
+                if l.trim().is_empty() {
+                    l.to_string()
+                } else {
+                    translated.next().unwrap_or_default()
+                }
+            })
+            .collect())
+    }
+}
+
+fn number_lines(lines: &[&str]) -> String {
+    lines
+        .iter()
+        .enumerate()
+        .map(|(index, line)| format!("{}: {}", index + 1, line))
+        .join("\n")
+}
+
+/// Parses the numbered lines of a reply into a map of line number to text. Numbers outside
+/// of 1..=expected are ignored. Unnumbered lines are treated as the continuation of the
+/// previous numbered line as models sometimes split a line in two, but only while later
+/// lines are still expected. Anything after the last line is chatter and dropped.
+fn parse_numbered_lines(reply: &str, expected: usize) -> BTreeMap<usize, String> {
+    let mut parsed: BTreeMap<usize, String> = BTreeMap::new();
+    let mut current: Option<usize> = None;
+
+    for line in reply.lines().filter(|l| !l.trim().is_empty()) {
+        if let Ok(Some(captures)) = NUMBERED_LINE_RE.captures(line) {
+            let num = captures
+                .name("num")
+                .or(captures.name("bracketed"))
+                .and_then(|num| num.as_str().parse::<usize>().ok())
+                .unwrap_or(0);
+
+            if (1..=expected).contains(&num) && !parsed.contains_key(&num) {
+                current = Some(insert_numbered_line(&mut parsed, num, &captures["text"], expected));
+            } else {
+                current = None;
+            }
+        } else if let Some(num) = current.filter(|num| *num < expected) {
+            let text = format!("{} {}", parsed[&num], line.trim());
+            current = Some(insert_numbered_line(&mut parsed, num, &text, expected));
+        }
+    }
+
+    return parsed;
+}
+
+/// Inserts the text of a line. Models sometimes merge lines, so the text from the marker of
+/// the next line, such as "2:", onwards is split off into that line. Returns the number of
+/// the last line inserted.
+fn insert_numbered_line(
+    parsed: &mut BTreeMap<usize, String>,
+    num: usize,
+    text: &str,
+    expected: usize,
+) -> usize {
+    let mut num = num;
+    let mut text = text;
+
+    loop {
+        let next = num + 1;
+
+        let marker = match next <= expected && !parsed.contains_key(&next) {
+            true => find_line_marker(text, next),
+            false => None,
+        };
+
+        match marker {
+            Some((start, end)) => {
+                parsed.insert(num, text[..start].trim().to_owned());
+                text = &text[end..];
+                num = next;
+            }
+            None => {
+                parsed.insert(num, text.trim().to_owned());
+                return num;
+            }
+        }
+    }
+}
+
+/// The start and end of a marker such as " 2: " or " [2] " inside the text.
+fn find_line_marker(text: &str, num: usize) -> Option<(usize, usize)> {
+    let marker = Regex::new(&format!(r"\s(?:\[{0}\]|{0}:)\s+", num)).ok()?;
+
+    marker.find(text).ok().flatten().map(|m| (m.start(), m.end()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_reply_with_every_line() {
+        let parsed = parse_numbered_lines("1: Hello.\n2: How are you?", 2);
+
+        assert_eq!(parsed.into_values().collect::<Vec<String>>(), vec!["Hello.", "How are you?"]);
+    }
+
+    #[test]
+    fn parse_reply_joins_split_lines() {
+        let parsed = parse_numbered_lines("1: Hello,\nmy friend.\n2: Bye.", 2);
+
+        assert_eq!(parsed.into_values().collect::<Vec<String>>(), vec!["Hello, my friend.", "Bye."]);
+    }
+
+    #[test]
+    fn parse_reply_ignores_unknown_numbers_and_chatter() {
+        let parsed = parse_numbered_lines("Here you go:\n1. Hello.\n[2] Bye.\n3: Extra", 2);
+
+        assert_eq!(parsed.into_values().collect::<Vec<String>>(), vec!["Hello.", "Bye."]);
+    }
+
+    #[test]
+    fn parse_reply_drops_chatter_after_last_line() {
+        let parsed = parse_numbered_lines("1: Hello,\nmy friend.\n2: Bye.\nI hope this helps!", 2);
+
+        assert_eq!(parsed.into_values().collect::<Vec<String>>(), vec!["Hello, my friend.", "Bye."]);
+    }
+
+    #[test]
+    fn parse_reply_splits_lines_merged_with_markers() {
+        let parsed = parse_numbered_lines("1: Hello. 2: How are you? [3] Fine.", 3);
+
+        assert_eq!(
+            parsed.into_values().collect::<Vec<String>>(),
+            vec!["Hello.", "How are you?", "Fine."]
+        );
+    }
+
+    #[test]
+    fn parse_reply_splits_markers_of_continuation_lines() {
+        let parsed = parse_numbered_lines("1: Hello,\nmy friend. 2: Bye.", 2);
+
+        assert_eq!(parsed.into_values().collect::<Vec<String>>(), vec!["Hello, my friend.", "Bye."]);
+    }
+
+    #[test]
+    fn parse_reply_joins_continuation_lines_starting_with_a_number() {
+        let parsed = parse_numbered_lines("1: It happened,\n2 years later.\n2: Bye.\n3: Fine.", 3);
+
+        assert_eq!(
+            parsed.into_values().collect::<Vec<String>>(),
+            vec!["It happened, 2 years later.", "Bye.", "Fine."]
+        );
+    }
+
+    #[test]
+    fn parse_reply_with_merged_lines() {
+        let parsed = parse_numbered_lines("1: Hello. How are you?", 2);
+
+        assert_eq!(parsed.len(), 1);
+    }
+}
@@ -0,0 +1,318 @@
+use std::ops::Range;
+
+use crate::translation::Translator;
+
+/// Cost added for every move that is not a one to one match of lines.
+const MERGE_SPLIT_PENALTY: f64 = 1.0;
+const INSERT_DELETE_PENALTY: f64 = 3.0;
+
+/// Characters ending a sentence or clause that are compared between source and target.
+const TERMINALS: [char; 6] = ['.', '!', '?', '…', ':', ';'];
+
+/// A group of source lines matched to a group of target lines.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bead {
+    pub source: Range<usize>,
+    pub target: Range<usize>,
+}
+
+impl Bead {
+    fn is_one_to_one(&self) -> bool {
+        self.source.len() == 1 && self.target.len() == 1
+    }
+}
+
+/// A change to the target lines.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Repair {
+    /// Splits a target line in two at the byte offset.
+    Split { target: usize, at: usize },
+    /// Merges a target line with the one following it.
+    Merge { target: usize },
+    /// Inserts a line before the target line.
+    Insert { target: usize, text: String },
+    /// Deletes a target line.
+    Delete { target: usize },
+    /// Replaces the text of a target line.
+    Edit { target: usize, text: String },
+}
+
+impl Repair {
+    pub fn apply(&self, target: &mut Vec<String>) {
+        match self {
+            Repair::Split { target: index, at } => {
+                let line = target[*index].to_owned();
+                let (first, second) = line.split_at(*at);
+                target[*index] = first.trim().to_owned();
+                target.insert(index + 1, second.trim().to_owned());
+            }
+            Repair::Merge { target: index } => {
+                if index + 1 < target.len() {
+                    let next = target.remove(index + 1);
+                    let line = &mut target[*index];
+                    if !line.is_empty() && !next.is_empty() {
+                        line.push(' ');
+                    }
+                    line.push_str(&next);
+                }
+            }
+            Repair::Insert { target: index, text } => target.insert(*index, text.to_owned()),
+            Repair::Delete { target: index } => {
+                target.remove(*index);
+            }
+            Repair::Edit { target: index, text } => target[*index] = text.to_owned(),
+        }
+    }
+}
+
+impl std::fmt::Display for Repair {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Repair::Split { target, .. } => write!(f, "Split target line [{}]", target + 1),
+            Repair::Merge { target } => {
+                write!(f, "Merge target lines [{}] and [{}]", target + 1, target + 2)
+            }
+            Repair::Insert { target, .. } => write!(f, "Insert a target line at [{}]", target + 1),
+            Repair::Delete { target } => write!(f, "Delete target line [{}]", target + 1),
+            Repair::Edit { target, .. } => write!(f, "Edit target line [{}]", target + 1),
+        }
+    }
+}
+
+/// Where the source and target lines stop lining up and the repair that most likely fixes it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Misalignment {
+    pub bead: Bead,
+    pub suggestion: Repair,
+}
+
+/// Matches source lines to target lines allowing lines to be merged, split, dropped or added.
+/// Lines are matched by how well their lengths agree with the overall length ratio and by
+/// their sentence punctuation.
+pub fn align_lines<S: AsRef<str>, T: AsRef<str>>(source: &[S], target: &[T]) -> Vec<Bead> {
+    let source: Vec<&str> = source.iter().map(|s| s.as_ref()).collect();
+    let target: Vec<&str> = target.iter().map(|t| t.as_ref()).collect();
+
+    let (n, m) = (source.len(), target.len());
+    let ratio = (total_chars(&target) + 1) as f64 / (total_chars(&source) + 1) as f64;
+
+    // cost[i][j] is the cheapest way to match the first i source and j target lines.
+    let mut cost = vec![vec![f64::INFINITY; m + 1]; n + 1];
+    let mut moves = vec![vec![(0, 0); m + 1]; n + 1];
+    cost[0][0] = 0.0;
+
+    for i in 0..=n {
+        for j in 0..=m {
+            if cost[i][j].is_infinite() {
+                continue;
+            }
+
+            let options: [(usize, usize, f64); 5] = [
+                (1, 1, 0.0),
+                (2, 1, MERGE_SPLIT_PENALTY),
+                (1, 2, MERGE_SPLIT_PENALTY),
+                (1, 0, INSERT_DELETE_PENALTY),
+                (0, 1, INSERT_DELETE_PENALTY),
+            ];
+
+            for (di, dj, penalty) in options {
+                if i + di > n || j + dj > m {
+                    continue;
+                }
+
+                let s = source[i..i + di].join(" ");
+                let t = target[j..j + dj].join(" ");
+                let c = cost[i][j] + penalty + match_cost(&s, &t, ratio);
+
+                if c < cost[i + di][j + dj] {
+                    cost[i + di][j + dj] = c;
+                    moves[i + di][j + dj] = (di, dj);
+                }
+            }
+        }
+    }
+
+    let mut beads = Vec::new();
+    let (mut i, mut j) = (n, m);
+
+    while i > 0 || j > 0 {
+        let (di, dj) = moves[i][j];
+        beads.push(Bead {
+            source: i - di..i,
+            target: j - dj..j,
+        });
+        i -= di;
+        j -= dj;
+    }
+
+    beads.reverse();
+    return beads;
+}
+
+/// Finds the first place where lines were most likely merged, split, dropped or added.
+pub fn find_misalignment<S: AsRef<str>, T: AsRef<str>>(
+    source: &[S],
+    target: &[T],
+) -> Option<Misalignment> {
+    let bead = align_lines(source, target)
+        .into_iter()
+        .find(|b| !b.is_one_to_one())?;
+
+    let suggestion = match (bead.source.len(), bead.target.len()) {
+        (2, 1) => {
+            let first = source[bead.source.start].as_ref().chars().count();
+            let second = source[bead.source.start + 1].as_ref().chars().count();
+            let proportion = first as f64 / (first + second).max(1) as f64;
+            let line = target[bead.target.start].as_ref();
+
+            match split_point(line, proportion) {
+                Some(at) => Repair::Split { target: bead.target.start, at },
+                None => Repair::Insert { target: bead.target.start + 1, text: String::new() },
+            }
+        }
+        (1, 2) => Repair::Merge { target: bead.target.start },
+        (1, 0) => Repair::Insert { target: bead.target.start, text: String::new() },
+        _ => Repair::Delete { target: bead.target.start },
+    };
+
+    Some(Misalignment { bead, suggestion })
+}
+
+/// The byte offset of the space where text is best split so the first part holds roughly
+/// proportion of its characters. Spaces following sentence punctuation are preferred.
+pub fn split_point(text: &str, proportion: f64) -> Option<usize> {
+    let total = text.chars().count() as f64;
+    let wanted = total * proportion;
+
+    text.char_indices()
+        .filter(|(_, c)| c.is_whitespace())
+        .map(|(at, _)| {
+            let position = text[..at].chars().count() as f64;
+            let after_terminal = text[..at].trim_end().ends_with(TERMINALS)
+                || text[..at].trim_end().ends_with(',');
+            let distance = (position - wanted).abs() / total.max(1.0);
+            let score = if after_terminal { distance } else { distance + 0.25 };
+            (at, score)
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(at, _)| at)
+}
+
+fn total_chars(lines: &[&str]) -> usize {
+    lines.iter().map(|l| l.chars().count()).sum()
+}
+
+fn match_cost(source: &str, target: &str, ratio: f64) -> f64 {
+    let source_len = source.chars().count() as f64;
+    let target_len = target.chars().count() as f64;
+
+    let length_cost = ((target_len + 1.0) / ((source_len + 1.0) * ratio)).ln().abs();
+
+    let terminal_diff = (count_terminals(source) as f64 - count_terminals(target) as f64).abs();
+    let last_differs = last_terminal(source) != last_terminal(target);
+
+    length_cost + 0.5 * terminal_diff + if last_differs { 0.3 } else { 0.0 }
+}
+
+fn count_terminals(text: &str) -> usize {
+    text.chars().filter(|c| TERMINALS.contains(c)).count()
+}
+
+fn last_terminal(text: &str) -> Option<char> {
+    text.trim_end()
+        .chars()
+        .last()
+        .filter(|c| TERMINALS.contains(c))
+}
+
+/// Called with the source lines and the translated lines when their counts differ. Returns
+/// the repaired translated lines.
+pub type RepairFn = Box<dyn FnMut(&[&str], Vec<String>) -> Result<Vec<String>, anyhow::Error>>;
+
+/// Hands the result of the wrapped translator to a repair function when it returns a
+/// different number of lines than it was given.
+pub struct RepairingTranslator {
+    translator: Box<dyn Translator>,
+    repair: RepairFn,
+}
+
+impl RepairingTranslator {
+    pub fn new(translator: Box<dyn Translator>, repair: RepairFn) -> Self {
+        RepairingTranslator { translator, repair }
+    }
+}
+
+impl Translator for RepairingTranslator {
+    fn translate(&mut self, source: &[&str]) -> Result<Vec<String>, anyhow::Error> {
+        let target = self.translator.translate(source)?;
+
+        if target.len() == source.len() {
+            Ok(target)
+        } else {
+            (self.repair)(source, target)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn aligns_equal_lines_one_to_one() {
+        let beads = align_lines(&["Eins.", "Zwei."], &["One.", "Two."]);
+
+        assert!(beads.iter().all(|b| b.is_one_to_one()));
+        assert_eq!(beads.len(), 2);
+    }
+
+    #[test]
+    fn finds_merged_lines() {
+        let source = [
+            "Wir sind heute im Schwarzwald unterwegs.",
+            "Ja.",
+            "Hier gibt es viele Trails.",
+            "Und viel Ärger.",
+        ];
+        let target = [
+            "Today we are in the Black Forest.",
+            "Yes. There are many trails here.",
+            "And a lot of trouble.",
+        ];
+
+        let misalignment = find_misalignment(&source, &target).unwrap();
+
+        assert_eq!(misalignment.bead, Bead { source: 1..3, target: 1..2 });
+        assert_eq!(misalignment.suggestion, Repair::Split { target: 1, at: 4 });
+    }
+
+    #[test]
+    fn finds_split_lines() {
+        let source = ["Was ist passiert? Keiner weiß es.", "Das ist alles."];
+        let target = ["What happened?", "Nobody knows.", "That is all."];
+
+        let misalignment = find_misalignment(&source, &target).unwrap();
+
+        assert_eq!(misalignment.bead, Bead { source: 0..1, target: 0..2 });
+        assert_eq!(misalignment.suggestion, Repair::Merge { target: 0 });
+    }
+
+    #[test]
+    fn split_point_prefers_punctuation() {
+        let at = split_point("Yes, sure. There are many trails here.", 0.2).unwrap();
+
+        assert_eq!(at, 10);
+    }
+
+    #[test]
+    fn repairs_apply_to_target() {
+        let mut target = vec!["One. Two.".to_owned(), "Three".to_owned(), "Four".to_owned()];
+
+        Repair::Split { target: 0, at: 4 }.apply(&mut target);
+        Repair::Merge { target: 2 }.apply(&mut target);
+        Repair::Insert { target: 0, text: "Zero".to_owned() }.apply(&mut target);
+        Repair::Delete { target: 1 }.apply(&mut target);
+
+        assert_eq!(target, vec!["Zero", "Two.", "Three Four"]);
+    }
+}
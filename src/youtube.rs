@@ -1,13 +1,13 @@
-use anyhow::{Context, Error};
+use anyhow::{anyhow, Context, Error};
 use fancy_regex::Regex;
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
 use srtlib::Subtitles;
-use std::io::{Read, Write};
-use std::process::{Command, Stdio};
 use url::Url;
 use youtube_dl::{SingleVideo, YoutubeDl, YoutubeDlOutput};
 
+use crate::subtitle::{format::SubtitleFormat, timedtext::TimedText, vtt::WebVtt};
+
 lazy_static! {
     static ref LOOKS_LIKE_LANGUAGE: Regex = Regex::new(r"^(?P<lang>[a-zA-Z]{2})-.*$").unwrap();
 }
@@ -23,36 +23,42 @@ pub fn info(url: &Url) -> Result<VideoInfo, Error> {
     }
 }
 
-pub fn download(url: &Url) -> Result<Subtitles, anyhow::Error> {
-    let subtitles = reqwest::blocking::get(url.to_owned())?
-        .text()
-        .context(format!("Failed to download {}", url))?;
+/// Downloads the subtitles of the choice and reads them in its format.
+pub fn download(choice: &SubtitleChoice) -> Result<Subtitles, anyhow::Error> {
+    let (format, subtitles) = download_text(choice)?;
+
+    return format.parse(&subtitles).context("Failed to parse subtitles file.");
+}
+
+/// Downloads the subtitles of a json3 or srv3 choice keeping the time of every word.
+/// Fails for formats without word timings.
+pub fn download_timed_text(choice: &SubtitleChoice) -> Result<TimedText, anyhow::Error> {
+    let (format, subtitles) = download_text(choice)?;
+
+    return format.parse_timed_text(&subtitles).context("Failed to parse subtitles file.");
+}
+
+/// Downloads the subtitles of a vtt choice as they are, keeping the cue settings and the
+/// blocks other than cues. Fails for other formats.
+pub fn download_vtt(choice: &SubtitleChoice) -> Result<WebVtt, anyhow::Error> {
+    let (format, subtitles) = download_text(choice)?;
 
-    let subtitles = convert_to_srt(subtitles.as_bytes())?;
+    if format != SubtitleFormat::Vtt {
+        return Err(anyhow!("Subtitles in the {} format are not WebVTT", choice.format));
+    }
 
-    return Subtitles::parse_from_str(subtitles).context("Failed to parse subtitles file.");
+    return WebVtt::parse(&subtitles).context("Failed to parse subtitles file.");
 }
 
-fn convert_to_srt(source: &[u8]) -> Result<String, anyhow::Error> {
-    let ffmpeg = Command::new("ffmpeg")
-        .stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .arg("-loglevel")
-        .arg("quiet")
-        .arg("-i")
-        .arg("pipe:0")
-        .arg("-f")
-        .arg("srt")
-        .arg("pipe:1")
-        .spawn()
-        .context("Unable to convert subtitles to SRT format. Ensure that ffmpeg is installed and on the PATH")?;
-
-    ffmpeg.stdin.unwrap().write_all(source)?;
-
-    let mut s = String::new();
-    ffmpeg.stdout.unwrap().read_to_string(&mut s)?;
-
-    Ok(s)
+fn download_text(choice: &SubtitleChoice) -> Result<(SubtitleFormat, String), anyhow::Error> {
+    let format = SubtitleFormat::from_name(&choice.format)
+        .ok_or(anyhow!("Subtitles in the {} format are not supported", choice.format))?;
+
+    let subtitles = reqwest::blocking::get(choice.location.to_owned())?
+        .text()
+        .context(format!("Failed to download {}", choice.location))?;
+
+    Ok((format, subtitles))
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -106,7 +112,7 @@ impl TryFrom<SingleVideo> for VideoInfo {
             subs.into_iter().try_fold(Vec::new(), |mut acc, (lang, s)| {
                 for choice in s.unwrap_or_default() {
                     if let (Some(subtitle_format), Some(url)) = (choice.ext, choice.url) {
-                        if subtitle_format == "vtt" || subtitle_format == "srt" {
+                        if SubtitleFormat::from_name(&subtitle_format).is_some() {
                             let location = Url::parse(&url)?;
                             let choice =
                                 SubtitleChoice::new(lang.to_owned(), subtitle_format, location);
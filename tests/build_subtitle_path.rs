@@ -18,7 +18,23 @@ fn should_return_file_path_if_it_has_srt_extension() {
 }
 
 #[test]
-#[should_panic(expected = "File must end with an srt extension!")]
+fn should_return_file_path_if_it_has_vtt_extension() {
+    let path = build_subtitle_path(
+        Some(PathBuf::from_str("/path/subs.vtt").unwrap()),
+        Some(PathBuf::from_str("/arg_path").unwrap()),
+        Some(PathBuf::from_str("/config_path").unwrap()), 
+        "title", 
+        &Some("de".to_owned()), 
+        &Some("channel".to_owned())).unwrap();
+
+    assert_eq!(
+        path, 
+        Path::new("/path/subs.vtt").to_path_buf()
+    )
+}
+
+#[test]
+#[should_panic(expected = "File must end with an srt or vtt extension!")]
 fn should_fail_if_file_path_does_not_have_srt_extension() {
     build_subtitle_path(
         Some(PathBuf::from_str("/path/subs.foo").unwrap()),
@@ -30,7 +46,7 @@ fn should_fail_if_file_path_does_not_have_srt_extension() {
 }
 
 #[test]
-#[should_panic(expected = "File must end with an srt extension!")]
+#[should_panic(expected = "File must end with an srt or vtt extension!")]
 fn should_fail_if_file_path_does_not_have_extension() {
     build_subtitle_path(
         Some(PathBuf::from_str("/path/subs").unwrap()),
@@ -41,6 +57,18 @@ fn should_fail_if_file_path_does_not_have_extension() {
         &Some("channel".to_owned())).unwrap();
 }
 
+#[test]
+#[should_panic(expected = "File must end with an srt or vtt extension!")]
+fn should_fail_if_file_path_has_read_only_extension() {
+    build_subtitle_path(
+        Some(PathBuf::from_str("/path/subs.json3").unwrap()),
+        Some(PathBuf::from_str("/arg_path").unwrap()),
+        Some(PathBuf::from_str("/config_path").unwrap()), 
+        "title", 
+        &Some("de".to_owned()), 
+        &Some("channel".to_owned())).unwrap();
+}
+
 
 
 #[test]
@@ -1,6 +1,7 @@
 mod common;
 
 use lang_tools::subtitle::clean::clean_subtitles;
+use lang_tools::tokenizer::UnicodeTokenizer;
 
 use crate::common::subtitles;
 
@@ -10,7 +11,7 @@ fn remove_opening_and_closing_html_elements() {
         "<i>Bin doch zufrieden.</i>\n<i>Mir geht’s doch gut.</i>",
     ]);
 
-    clean_subtitles(&mut subtitles);
+    clean_subtitles(&mut subtitles, &UnicodeTokenizer);
 
     assert_eq!(subtitles[0].text, "Bin doch zufrieden.\nMir geht’s doch gut.")
 }
@@ -19,7 +20,7 @@ fn remove_opening_and_closing_html_elements() {
 fn remove_trailing_spaces() {
     let mut subtitles = subtitles(vec!["Ich erinnere mich nur noch an den Rauch   "]);
 
-    clean_subtitles(&mut subtitles);
+    clean_subtitles(&mut subtitles, &UnicodeTokenizer);
 
     assert_eq!(subtitles[0].text, "Ich erinnere mich nur noch an den Rauch")
 }
@@ -28,7 +29,7 @@ fn remove_trailing_spaces() {
 fn remove_leading_spaces() {
     let mut subtitles = subtitles(vec!["     Ich erinnere mich nur noch an den Rauch"]);
 
-    clean_subtitles(&mut subtitles);
+    clean_subtitles(&mut subtitles, &UnicodeTokenizer);
 
     assert_eq!(subtitles[0].text, "Ich erinnere mich nur noch an den Rauch")
 }
@@ -37,7 +38,7 @@ fn remove_leading_spaces() {
 fn remove_multiple_inner_spaces() {
     let mut subtitles = subtitles(vec!["Ich erinnere    mich  nur noch an den Rauch"]);
 
-    clean_subtitles(&mut subtitles);
+    clean_subtitles(&mut subtitles, &UnicodeTokenizer);
 
     assert_eq!(subtitles[0].text, "Ich erinnere mich nur noch an den Rauch")
 }
@@ -48,7 +49,7 @@ fn remove_leading_space_second_line() {
         "Der vielleicht glücklichste Tag\n der Deutschen: der 9. November '89.",
     ]);
 
-    clean_subtitles(&mut subtitles);
+    clean_subtitles(&mut subtitles, &UnicodeTokenizer);
 
     assert_eq!(
         subtitles[0].text,
@@ -62,7 +63,7 @@ fn remove_space_carriage_return_space() {
         "Der A350 macht unglaublich viel \r Spaß dieses Flugzeug zu fliegen.",
     ]);
 
-    clean_subtitles(&mut subtitles);
+    clean_subtitles(&mut subtitles, &UnicodeTokenizer);
 
     assert_eq!(
         subtitles[0].text,
@@ -77,7 +78,7 @@ fn adjust_subtitle_ending_hyphen() {
         "Kollektiv irgendwas gedreht.",
     ]);
 
-    clean_subtitles(&mut subtitles);
+    clean_subtitles(&mut subtitles, &UnicodeTokenizer);
 
     assert_eq!(subtitles[0].text, "Und die haben vielleicht mal für Y-Kollektiv");
     assert_eq!(subtitles[1].text, "irgendwas gedreht.");
@@ -89,7 +90,7 @@ fn adjust_hyphen_newline() {
         "dass 70% der Insel und des um-\nliegenden Archipels zerstört wurden."
     ]);
 
-    clean_subtitles(&mut subtitles);
+    clean_subtitles(&mut subtitles, &UnicodeTokenizer);
 
     assert_eq!(subtitles[0].text, "dass 70% der Insel und des umliegenden\nArchipels zerstört wurden.");
 }
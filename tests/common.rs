@@ -15,5 +15,5 @@ pub fn subtitles(subtitles: Vec<&str>) -> Subtitles {
         })
         .collect();
 
-    return srtlib::Subtitles::new_from_vec(x)
+    srtlib::Subtitles::new_from_vec(x)
 }
\ No newline at end of file
@@ -0,0 +1,160 @@
+use lang_tools::{
+    translation::{Translation, Translator},
+    translator::deepl::{DeeplConfig, DeeplTranslator, Formality},
+};
+use mockito::{Matcher, Server};
+use serde_json::{json, Value};
+use url::Url;
+
+fn config(server: &Server) -> DeeplConfig {
+    DeeplConfig {
+        auth_key: "secret:fx".to_owned(),
+        url: Some(Url::parse(&server.url()).unwrap()),
+        source_lang: Some("DE".to_owned()),
+        target_lang: "EN-US".to_owned(),
+        formality: None,
+        glossary_id: None,
+    }
+}
+
+// Responds with each of the requested texts in upper case.
+fn uppercase_translations(request: &mockito::Request) -> Vec<u8> {
+    let body: Value = serde_json::from_slice(request.body().unwrap()).unwrap();
+    let translations: Vec<Value> = body["text"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| json!({"detected_source_language": "DE", "text": t.as_str().unwrap().to_uppercase()}))
+        .collect();
+
+    serde_json::to_vec(&json!({ "translations": translations })).unwrap()
+}
+
+#[test]
+fn should_translate_lines() {
+    let mut server = Server::new();
+    let mock = server
+        .mock("POST", "/v2/translate")
+        .match_header("authorization", "DeepL-Auth-Key secret:fx")
+        .match_body(Matcher::PartialJson(json!({
+            "text": ["Guten Morgen.", "Wie geht's?"],
+            "source_lang": "DE",
+            "target_lang": "EN-US"
+        })))
+        .with_body_from_request(uppercase_translations)
+        .create();
+
+    let mut translator = DeeplTranslator::new(config(&server)).unwrap();
+    let translations =
+        Translation::translate("Guten Morgen.\nWie geht's?", &mut translator).unwrap();
+
+    mock.assert();
+    assert_eq!(translations[0].target_text, "GUTEN MORGEN.");
+    assert_eq!(translations[1].target_text, "WIE GEHT'S?");
+}
+
+#[test]
+fn should_batch_requests() {
+    let mut server = Server::new();
+    let mock = server
+        .mock("POST", "/v2/translate")
+        .with_body_from_request(uppercase_translations)
+        .expect(3)
+        .create();
+
+    let source: Vec<String> = (0..120).map(|i| format!("Satz {}", i)).collect();
+    let source: Vec<&str> = source.iter().map(|s| s.as_str()).collect();
+
+    let mut translator = DeeplTranslator::new(config(&server)).unwrap();
+    let translated = translator.translate(&source).unwrap();
+
+    mock.assert();
+    assert_eq!(translated.len(), 120);
+    assert_eq!(translated[119], "SATZ 119");
+}
+
+#[test]
+fn should_keep_empty_lines_without_sending_them() {
+    let mut server = Server::new();
+    let mock = server
+        .mock("POST", "/v2/translate")
+        .match_body(Matcher::PartialJson(json!({ "text": ["Eins", "Zwei"] })))
+        .with_body_from_request(uppercase_translations)
+        .create();
+
+    let mut translator = DeeplTranslator::new(config(&server)).unwrap();
+    let translated = translator.translate(&["Eins", "", "Zwei"]).unwrap();
+
+    mock.assert();
+    assert_eq!(translated, vec!["EINS", "", "ZWEI"]);
+}
+
+#[test]
+fn should_send_glossary_and_formality() {
+    let mut server = Server::new();
+    let mock = server
+        .mock("POST", "/v2/translate")
+        .match_body(Matcher::PartialJson(json!({
+            "glossary_id": "glossary",
+            "formality": "prefer_less"
+        })))
+        .with_body_from_request(uppercase_translations)
+        .create();
+
+    let config = DeeplConfig {
+        glossary_id: Some("glossary".to_owned()),
+        formality: Some(Formality::PreferLess),
+        ..config(&server)
+    };
+
+    let mut translator = DeeplTranslator::new(config).unwrap();
+    translator.translate(&["Eins"]).unwrap();
+
+    mock.assert();
+}
+
+#[test]
+fn should_require_source_lang_for_glossary() {
+    let server = Server::new();
+    let config = DeeplConfig {
+        glossary_id: Some("glossary".to_owned()),
+        source_lang: None,
+        ..config(&server)
+    };
+
+    assert!(DeeplTranslator::new(config).is_err());
+}
+
+#[test]
+#[should_panic(expected = "DeepL responded with [456 <unknown status code>]: Quota exceeded")]
+fn should_fail_on_error_status() {
+    let mut server = Server::new();
+    server
+        .mock("POST", "/v2/translate")
+        .with_status(456)
+        .with_body("Quota exceeded")
+        .create();
+
+    let mut translator = DeeplTranslator::new(config(&server)).unwrap();
+    translator.translate(&["Eins"]).unwrap();
+}
+
+#[test]
+fn should_keep_path_of_configured_url() {
+    let mut server = Server::new();
+    let mock = server
+        .mock("POST", "/deepl/v2/translate")
+        .with_body_from_request(uppercase_translations)
+        .create();
+
+    let config = DeeplConfig {
+        url: Some(Url::parse(&format!("{}/deepl", server.url())).unwrap()),
+        ..config(&server)
+    };
+
+    let mut translator = DeeplTranslator::new(config).unwrap();
+    let translations = Translation::translate("Hallo.", &mut translator).unwrap();
+
+    mock.assert();
+    assert_eq!(translations[0].target_text, "HALLO.");
+}
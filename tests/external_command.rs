@@ -0,0 +1,56 @@
+#![cfg(unix)]
+
+use lang_tools::{
+    translation::{Translation, Translator},
+    translator::command::{ExternalCommandConfig, ExternalCommandTranslator},
+};
+
+fn translator(command: &str, timeout_secs: Option<u64>) -> ExternalCommandTranslator {
+    ExternalCommandTranslator::new(ExternalCommandConfig {
+        command: command.to_owned(),
+        timeout_secs,
+    })
+}
+
+#[test]
+fn should_pipe_lines_through_command() {
+    let mut translator = translator("tr a-z A-Z", None);
+
+    let translations =
+        Translation::translate("Guten Morgen.\n\nWie geht's?", &mut translator).unwrap();
+
+    assert_eq!(translations[0].target_text, "GUTEN MORGEN.");
+    assert_eq!(translations[1].target_text, "");
+    assert_eq!(translations[2].target_text, "WIE GEHT'S?");
+}
+
+#[test]
+fn should_handle_output_larger_than_pipe_buffer() {
+    let source: Vec<String> = (0..20000).map(|i| format!("Das ist der Satz Nummer {}", i)).collect();
+    let source: Vec<&str> = source.iter().map(|s| s.as_str()).collect();
+
+    let translated = translator("cat", None).translate(&source).unwrap();
+
+    assert_eq!(translated.len(), 20000);
+    assert_eq!(translated[19999], "Das ist der Satz Nummer 19999");
+}
+
+#[test]
+#[should_panic(expected = "failed with exit status: 3: no model installed")]
+fn should_report_stderr_when_command_fails() {
+    translator("echo 'no model installed' >&2; exit 3", None)
+        .translate(&["Eins"])
+        .unwrap();
+}
+
+#[test]
+#[should_panic(expected = "Timed out after 1 seconds")]
+fn should_kill_command_after_timeout() {
+    translator("sleep 10", Some(1)).translate(&["Eins"]).unwrap();
+}
+
+#[test]
+#[should_panic(expected = "Sent 2 lines to the translator but received 1 translations")]
+fn should_fail_when_command_drops_lines() {
+    translator("head -n 1", None).translate(&["Eins", "Zwei"]).unwrap();
+}
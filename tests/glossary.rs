@@ -0,0 +1,44 @@
+use std::{fs, path::PathBuf};
+
+use lang_tools::{glossary::Glossary, memory::LanguagePair, translation::Translation};
+
+fn glossary_dir() -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("lang-tools-glossary-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn should_load_glossary_of_language_pair() {
+    let pair = LanguagePair::new(Some("de".to_owned()), Some("en".to_owned()));
+    let path = Glossary::path(&glossary_dir(), &pair);
+
+    fs::write(
+        &path,
+        "- source: Y-Kollektiv\n- source: Mountainbiker\n  target: mountain biker\n  variants: [mountainbiker]\n",
+    )
+    .unwrap();
+
+    let glossary = Glossary::load(&path).unwrap().unwrap();
+
+    let mut translations = vec![
+        Translation::new("Das Y-Kollektiv fährt.", "The Y Collective rides."),
+        Translation::new("Ein Mountainbiker.", "A mountainbiker."),
+    ];
+
+    assert_eq!(glossary.apply(&mut translations), 1);
+    assert_eq!(translations[1].target_text, "A mountain biker.");
+
+    let mismatches = glossary.check(&translations);
+
+    assert_eq!(mismatches.len(), 1);
+    assert_eq!(mismatches[0].line, 0);
+    assert_eq!(mismatches[0].expected, "Y-Kollektiv");
+}
+
+#[test]
+fn should_return_none_without_glossary_file() {
+    let pair = LanguagePair::new(Some("xx".to_owned()), None);
+
+    assert!(Glossary::load(&Glossary::path(&glossary_dir(), &pair)).unwrap().is_none());
+}
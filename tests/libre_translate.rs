@@ -0,0 +1,130 @@
+use lang_tools::{
+    translation::{Translation, Translator},
+    translator::libre_translate::{LibreTranslateConfig, LibreTranslateTranslator},
+};
+use mockito::{Matcher, Server};
+use serde_json::{json, Value};
+use url::Url;
+
+fn config(server: &Server) -> LibreTranslateConfig {
+    LibreTranslateConfig {
+        url: Url::parse(&server.url()).unwrap(),
+        api_key: Some("secret".to_owned()),
+        source_lang: Some("de".to_owned()),
+        target_lang: "en".to_owned(),
+        max_request_bytes: None,
+    }
+}
+
+// Responds with each of the requested texts in upper case.
+fn uppercase_translations(request: &mockito::Request) -> Vec<u8> {
+    let body: Value = serde_json::from_slice(request.body().unwrap()).unwrap();
+    let translations: Vec<String> = body["q"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t.as_str().unwrap().to_uppercase())
+        .collect();
+
+    serde_json::to_vec(&json!({ "translatedText": translations })).unwrap()
+}
+
+#[test]
+fn should_translate_lines() {
+    let mut server = Server::new();
+    let mock = server
+        .mock("POST", "/translate")
+        .match_body(Matcher::Json(json!({
+            "q": ["Guten Morgen.", "Wie geht's?"],
+            "source": "de",
+            "target": "en",
+            "format": "text",
+            "api_key": "secret"
+        })))
+        .with_body_from_request(uppercase_translations)
+        .create();
+
+    let mut translator = LibreTranslateTranslator::new(config(&server)).unwrap();
+    let translations =
+        Translation::translate("Guten Morgen.\n\nWie geht's?", &mut translator).unwrap();
+
+    mock.assert();
+    assert_eq!(translations[0].target_text, "GUTEN MORGEN.");
+    assert_eq!(translations[1].target_text, "");
+    assert_eq!(translations[2].target_text, "WIE GEHT'S?");
+}
+
+#[test]
+fn should_detect_source_language_if_missing() {
+    let mut server = Server::new();
+    let mock = server
+        .mock("POST", "/translate")
+        .match_body(Matcher::PartialJson(json!({ "source": "auto" })))
+        .with_body_from_request(uppercase_translations)
+        .create();
+
+    let config = LibreTranslateConfig {
+        source_lang: None,
+        ..config(&server)
+    };
+
+    let mut translator = LibreTranslateTranslator::new(config).unwrap();
+    translator.translate(&["Eins"]).unwrap();
+
+    mock.assert();
+}
+
+#[test]
+fn should_batch_by_request_size() {
+    let mut server = Server::new();
+    let mock = server
+        .mock("POST", "/translate")
+        .with_body_from_request(uppercase_translations)
+        .expect(2)
+        .create();
+
+    let config = LibreTranslateConfig {
+        max_request_bytes: Some(10),
+        ..config(&server)
+    };
+
+    let mut translator = LibreTranslateTranslator::new(config).unwrap();
+    let translated = translator.translate(&["Eins", "Zwei", "Drei"]).unwrap();
+
+    mock.assert();
+    assert_eq!(translated, vec!["EINS", "ZWEI", "DREI"]);
+}
+
+#[test]
+#[should_panic(expected = "LibreTranslate responded with [403 Forbidden]: Invalid API key")]
+fn should_report_server_error() {
+    let mut server = Server::new();
+    server
+        .mock("POST", "/translate")
+        .with_status(403)
+        .with_body(r#"{"error": "Invalid API key"}"#)
+        .create();
+
+    let mut translator = LibreTranslateTranslator::new(config(&server)).unwrap();
+    translator.translate(&["Eins"]).unwrap();
+}
+
+#[test]
+fn should_keep_path_of_configured_url() {
+    let mut server = Server::new();
+    let mock = server
+        .mock("POST", "/libre/translate")
+        .with_body_from_request(uppercase_translations)
+        .create();
+
+    let config = LibreTranslateConfig {
+        url: Url::parse(&format!("{}/libre", server.url())).unwrap(),
+        ..config(&server)
+    };
+
+    let mut translator = LibreTranslateTranslator::new(config).unwrap();
+    let translations = Translation::translate("Hallo.", &mut translator).unwrap();
+
+    mock.assert();
+    assert_eq!(translations[0].target_text, "HALLO.");
+}
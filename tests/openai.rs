@@ -0,0 +1,152 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use lang_tools::{
+    translation::{Translation, Translator},
+    translator::openai::{OpenAiConfig, OpenAiTranslator},
+};
+use mockito::{Matcher, Server};
+use serde_json::{json, Value};
+use url::Url;
+
+fn config(server: &Server) -> OpenAiConfig {
+    OpenAiConfig {
+        url: Url::parse(&format!("{}/v1", server.url())).unwrap(),
+        api_key: Some("secret".to_owned()),
+        model: "local".to_owned(),
+        source_lang: "German".to_owned(),
+        target_lang: "English".to_owned(),
+        batch_size: None,
+        context_lines: None,
+        max_retries: None,
+        temperature: None,
+        timeout_secs: None,
+    }
+}
+
+fn reply(content: &str) -> Vec<u8> {
+    serde_json::to_vec(&json!({
+        "choices": [{ "message": { "role": "assistant", "content": content } }]
+    }))
+    .unwrap()
+}
+
+// Replies with the numbered lines of the prompt in upper case.
+fn uppercase_reply(request: &mockito::Request) -> Vec<u8> {
+    let body: Value = serde_json::from_slice(request.body().unwrap()).unwrap();
+    let prompt = body["messages"][1]["content"].as_str().unwrap();
+
+    let lines: Vec<String> = prompt
+        .split("Translate:\n")
+        .nth(1)
+        .unwrap()
+        .split("\n\n")
+        .next()
+        .unwrap()
+        .lines()
+        .map(|l| l.to_uppercase())
+        .collect();
+
+    reply(&lines.join("\n"))
+}
+
+#[test]
+fn should_translate_numbered_lines() {
+    let mut server = Server::new();
+    let mock = server
+        .mock("POST", "/v1/chat/completions")
+        .match_header("authorization", "Bearer secret")
+        .match_body(Matcher::PartialJson(json!({ "model": "local" })))
+        .with_body_from_request(uppercase_reply)
+        .create();
+
+    let mut translator = OpenAiTranslator::new(config(&server)).unwrap();
+    let translations =
+        Translation::translate("Guten Morgen.\n\nWie geht's?", &mut translator).unwrap();
+
+    mock.assert();
+    assert_eq!(translations[0].target_text, "GUTEN MORGEN.");
+    assert_eq!(translations[1].target_text, "");
+    assert_eq!(translations[2].target_text, "WIE GEHT'S?");
+}
+
+#[test]
+fn should_send_surrounding_lines_as_context() {
+    let mut server = Server::new();
+    let mock = server
+        .mock("POST", "/v1/chat/completions")
+        .match_body(Matcher::Regex(
+            r"Context before:\\nEins\\nZwei\\n\\nTranslate:\\n1: Drei\\n2: Vier\\n\\nContext after:\\nFünf".to_owned(),
+        ))
+        .with_body_from_request(uppercase_reply)
+        .create();
+    let other = server
+        .mock("POST", "/v1/chat/completions")
+        .with_body_from_request(uppercase_reply)
+        .expect(2)
+        .create();
+
+    let config = OpenAiConfig {
+        batch_size: Some(2),
+        context_lines: Some(2),
+        ..config(&server)
+    };
+
+    let mut translator = OpenAiTranslator::new(config).unwrap();
+    let translated = translator.translate(&["Eins", "Zwei", "Drei", "Vier", "Fünf"]).unwrap();
+
+    mock.assert();
+    other.assert();
+    assert_eq!(translated, vec!["EINS", "ZWEI", "DREI", "VIER", "FÜNF"]);
+}
+
+#[test]
+fn should_retry_when_lines_are_merged() {
+    let calls = AtomicUsize::new(0);
+    let mut server = Server::new();
+    let mock = server
+        .mock("POST", "/v1/chat/completions")
+        .with_body_from_request(move |request| {
+            if calls.fetch_add(1, Ordering::SeqCst) == 0 {
+                reply("1: GUTEN MORGEN. WIE GEHT'S?")
+            } else {
+                uppercase_reply(request)
+            }
+        })
+        .expect(2)
+        .create();
+
+    let mut translator = OpenAiTranslator::new(config(&server)).unwrap();
+    let translated = translator.translate(&["Guten Morgen.", "Wie geht's?"]).unwrap();
+
+    mock.assert();
+    assert_eq!(translated, vec!["GUTEN MORGEN.", "WIE GEHT'S?"]);
+}
+
+#[test]
+fn should_translate_missing_lines_one_at_a_time() {
+    let mut server = Server::new();
+    // Always drops the second line unless it is the only line sent.
+    let mock = server
+        .mock("POST", "/v1/chat/completions")
+        .with_body_from_request(|request| {
+            let body = String::from_utf8(request.body().unwrap().to_owned()).unwrap();
+            if body.contains("2: Zwei") {
+                reply("1: ONE\n3: THREE")
+            } else {
+                reply("1: TWO")
+            }
+        })
+        .expect(4)
+        .create();
+
+    let config = OpenAiConfig {
+        max_retries: Some(2),
+        ..config(&server)
+    };
+
+    let mut translator = OpenAiTranslator::new(config).unwrap();
+    let translated = translator.translate(&["Eins", "Zwei", "Drei"]).unwrap();
+
+    mock.assert();
+    assert_eq!(translated, vec!["ONE", "TWO", "THREE"]);
+}
@@ -0,0 +1,36 @@
+use lang_tools::translation::{Translation, Translator};
+
+struct UppercaseTranslator;
+
+impl Translator for UppercaseTranslator {
+    fn translate(&mut self, source: &[&str]) -> Result<Vec<String>, anyhow::Error> {
+        Ok(source.iter().map(|l| l.to_uppercase()).collect())
+    }
+}
+
+struct DroppingTranslator;
+
+impl Translator for DroppingTranslator {
+    fn translate(&mut self, source: &[&str]) -> Result<Vec<String>, anyhow::Error> {
+        Ok(source.iter().skip(1).map(|l| l.to_string()).collect())
+    }
+}
+
+#[test]
+fn should_pair_each_source_line_with_translated_line() {
+    let translations =
+        Translation::translate("eins zwei\n\ndrei", &mut UppercaseTranslator).unwrap();
+
+    let pairs: Vec<(&str, &str)> = translations
+        .iter()
+        .map(|t| (t.source_text.as_str(), t.target_text.as_str()))
+        .collect();
+
+    assert_eq!(pairs, vec![("eins zwei", "EINS ZWEI"), ("", ""), ("drei", "DREI")]);
+}
+
+#[test]
+#[should_panic(expected = "There are 2 lines in source and 1 lines in target")]
+fn should_fail_if_translator_drops_lines() {
+    Translation::translate("eins\nzwei", &mut DroppingTranslator).unwrap();
+}
@@ -0,0 +1,103 @@
+use std::{fs, path::PathBuf};
+
+use lang_tools::{
+    memory::{LanguagePair, MemoryTranslator, TranslationMemory},
+    translation::{Translation, Translator},
+};
+
+fn memory_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir()
+        .join(format!("lang-tools-tm-{}", std::process::id()))
+        .join(format!("{}.json", name));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+fn de_en() -> LanguagePair {
+    LanguagePair::new(Some("de".to_owned()), Some("en".to_owned()))
+}
+
+#[test]
+fn should_find_recorded_translation_ignoring_whitespace() {
+    let mut memory = TranslationMemory::load(&memory_path("lookup")).unwrap();
+
+    memory.record(&de_en(), &[Translation::new("Hallo  Welt", "Hello world")]);
+
+    assert_eq!(memory.lookup(&de_en(), " Hallo Welt "), Some("Hello world".to_owned()));
+    assert_eq!(memory.lookup(&LanguagePair::new(Some("de".to_owned()), None), "Hallo Welt"), None);
+}
+
+#[test]
+fn should_save_and_load_entries() {
+    let path = memory_path("save");
+    let mut memory = TranslationMemory::load(&path).unwrap();
+    memory.record(&de_en(), &[Translation::new("Hallo", "Hello"), Translation::new("", "")]);
+    memory.save().unwrap();
+
+    let mut memory = TranslationMemory::load(&path).unwrap();
+
+    assert_eq!(memory.len(), 1);
+    assert_eq!(memory.lookup(&de_en(), "Hallo"), Some("Hello".to_owned()));
+}
+
+#[test]
+fn should_only_translate_lines_missing_from_memory() {
+    let path = memory_path("translator");
+    let mut memory = TranslationMemory::load(&path).unwrap();
+    memory.record(&de_en(), &[Translation::new("Willkommen zurück!", "Welcome back!")]);
+
+    let mut translator = MemoryTranslator::new(memory, de_en(), Box::new(UppercaseTranslator));
+
+    let translated = translator
+        .translate(&["Willkommen zurück!", "", "Heute geht es um Wölfe."])
+        .unwrap();
+
+    assert_eq!(translated, vec!["Welcome back!", "", "HEUTE GEHT ES UM WÖLFE."]);
+
+    let mut memory = TranslationMemory::load(&path).unwrap();
+    assert_eq!(
+        memory.lookup(&de_en(), "Heute geht es um Wölfe."),
+        Some("HEUTE GEHT ES UM WÖLFE.".to_owned())
+    );
+}
+
+// Only expects the line that is missing from the memory.
+struct UppercaseTranslator;
+
+impl Translator for UppercaseTranslator {
+    fn translate(&mut self, source: &[&str]) -> Result<Vec<String>, anyhow::Error> {
+        assert_eq!(source, ["Heute geht es um Wölfe."]);
+        Ok(source.iter().map(|l| l.to_uppercase()).collect())
+    }
+}
+
+#[test]
+fn should_search_source_and_target() {
+    let mut memory = TranslationMemory::load(&memory_path("search")).unwrap();
+    memory.record(
+        &de_en(),
+        &[
+            Translation::new("Der Wald", "The forest"),
+            Translation::new("Die Stadt", "The city"),
+        ],
+    );
+
+    assert_eq!(memory.search("wald").len(), 1);
+    assert_eq!(memory.search("CITY").len(), 1);
+    assert_eq!(memory.search("the").len(), 2);
+}
+
+#[test]
+fn should_prune_matching_entries() {
+    let mut memory = TranslationMemory::load(&memory_path("prune")).unwrap();
+    memory.record(&de_en(), &[Translation::new("Eins", "One")]);
+    memory.record(
+        &LanguagePair::new(Some("fr".to_owned()), Some("en".to_owned())),
+        &[Translation::new("Un", "One")],
+    );
+
+    let removed = memory.prune(|e| e.source_lang == "fr");
+
+    assert_eq!(removed, 1);
+    assert_eq!(memory.len(), 1);
+}